@@ -0,0 +1,265 @@
+use std::fmt;
+use std::str::FromStr;
+
+// Everything that can be done from a key, or from the command palette
+#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Debug)]
+pub enum Action {
+    Quit,
+    Help,
+    Welcome,
+    Palette,
+    Exit,
+    Enter,
+    Left,
+    Right,
+    Up,
+    Down,
+    PageUp,
+    PageDown,
+    Top,
+    Bottom,
+    WordLeft,
+    WordRight,
+    LineStart,
+    LineEnd,
+    SelectLeft,
+    SelectRight,
+    SelectUp,
+    SelectDown,
+    SelectWordLeft,
+    SelectWordRight,
+    SelectLineStart,
+    SelectLineEnd,
+    SelectAll,
+    Backspace,
+    Delete,
+    Newline,
+    Indent,
+    Undo,
+    Redo,
+    Copy,
+    Cut,
+    Paste,
+    ToggleLineNumbers,
+    ToggleWrap,
+    ChangeTheme,
+    GrowRequest,
+    ShrinkRequest,
+    ToggleStacked,
+    ToggleZoom,
+    ToggleTabs,
+    ToggleTiming,
+    ExternalEditor,
+    FormatJson,
+    MinifyJson,
+    Send,
+    NewTab,
+    DeleteTab,
+    SwitchEnvironment,
+    OpenCollectionItem,
+    ImportCurl,
+    OpenWorkflows,
+    CopyBody,
+    CopyHeaders,
+    CopyUrl,
+    CopyCurl,
+    CopyNode,
+}
+
+pub struct ActionInfo {
+    pub action: Action,
+    // The name used in keymap.json
+    pub name: &'static str,
+    pub title: &'static str,
+    // Whether it's listed in the command palette. Moving and editing only
+    // make sense as keys.
+    pub command: bool,
+}
+
+const fn info(
+    action: Action,
+    name: &'static str,
+    title: &'static str,
+    command: bool,
+) -> ActionInfo {
+    ActionInfo {
+        action,
+        name,
+        title,
+        command,
+    }
+}
+
+// The registry of actions. New actions go here to get a keymap name and,
+// for commands, a place in the palette.
+pub const ACTIONS: [ActionInfo; 60] = [
+    info(Action::Quit, "quit", "Quit", true),
+    info(Action::Help, "help", "Show key bindings", true),
+    info(Action::Welcome, "welcome", "Show the welcome screen", true),
+    info(Action::Palette, "palette", "Command palette", false),
+    info(Action::Exit, "exit", "Stop editing", false),
+    info(Action::Enter, "enter", "Enter the pane", false),
+    info(Action::Left, "left", "Left", false),
+    info(Action::Right, "right", "Right", false),
+    info(Action::Up, "up", "Up", false),
+    info(Action::Down, "down", "Down", false),
+    info(Action::PageUp, "page-up", "Page up", false),
+    info(Action::PageDown, "page-down", "Page down", false),
+    info(Action::Top, "top", "Go to the top", false),
+    info(Action::Bottom, "bottom", "Go to the bottom", false),
+    info(Action::WordLeft, "word-left", "Word left", false),
+    info(Action::WordRight, "word-right", "Word right", false),
+    info(Action::LineStart, "line-start", "Start of line", false),
+    info(Action::LineEnd, "line-end", "End of line", false),
+    info(Action::SelectLeft, "select-left", "Select left", false),
+    info(Action::SelectRight, "select-right", "Select right", false),
+    info(Action::SelectUp, "select-up", "Select up", false),
+    info(Action::SelectDown, "select-down", "Select down", false),
+    info(
+        Action::SelectWordLeft,
+        "select-word-left",
+        "Select word left",
+        false,
+    ),
+    info(
+        Action::SelectWordRight,
+        "select-word-right",
+        "Select word right",
+        false,
+    ),
+    info(
+        Action::SelectLineStart,
+        "select-line-start",
+        "Select to start of line",
+        false,
+    ),
+    info(
+        Action::SelectLineEnd,
+        "select-line-end",
+        "Select to end of line",
+        false,
+    ),
+    info(Action::SelectAll, "select-all", "Select all", false),
+    info(Action::Backspace, "backspace", "Delete backwards", false),
+    info(Action::Delete, "delete", "Delete forwards", false),
+    info(Action::Newline, "newline", "New line", false),
+    info(Action::Indent, "indent", "Indent", false),
+    info(Action::Undo, "undo", "Undo", false),
+    info(Action::Redo, "redo", "Redo", false),
+    info(Action::Copy, "copy", "Copy selection", false),
+    info(Action::Cut, "cut", "Cut selection", false),
+    info(Action::Paste, "paste", "Paste", false),
+    info(
+        Action::ToggleLineNumbers,
+        "toggle-line-numbers",
+        "Toggle line numbers",
+        true,
+    ),
+    info(
+        Action::ToggleWrap,
+        "toggle-wrap",
+        "Toggle response wrapping",
+        true,
+    ),
+    info(Action::ChangeTheme, "change-theme", "Change theme", true),
+    info(
+        Action::GrowRequest,
+        "grow-request",
+        "Make the request pane bigger",
+        true,
+    ),
+    info(
+        Action::ShrinkRequest,
+        "shrink-request",
+        "Make the request pane smaller",
+        true,
+    ),
+    info(
+        Action::ToggleStacked,
+        "toggle-stacked",
+        "Toggle side by side or stacked layout",
+        true,
+    ),
+    info(
+        Action::ToggleZoom,
+        "toggle-zoom",
+        "Show only the focused side",
+        true,
+    ),
+    info(Action::ToggleTabs, "toggle-tabs", "Show or hide tabs", true),
+    info(
+        Action::ToggleTiming,
+        "toggle-timing",
+        "Show or hide the timing chart",
+        true,
+    ),
+    info(
+        Action::ExternalEditor,
+        "external-editor",
+        "Edit in external editor",
+        true,
+    ),
+    info(Action::FormatJson, "format-json", "Format JSON body", true),
+    info(Action::MinifyJson, "minify-json", "Minify JSON body", true),
+    info(Action::Send, "send", "Send request", true),
+    info(Action::NewTab, "new-tab", "New tab", true),
+    info(Action::DeleteTab, "delete-tab", "Close tab", true),
+    info(
+        Action::SwitchEnvironment,
+        "switch-environment",
+        "Switch environment",
+        true,
+    ),
+    info(
+        Action::OpenCollectionItem,
+        "open-collection-item",
+        "Open request from collection",
+        true,
+    ),
+    info(
+        Action::ImportCurl,
+        "import-curl",
+        "Import curl command",
+        true,
+    ),
+    info(
+        Action::OpenWorkflows,
+        "open-workflows",
+        "Run workflow",
+        true,
+    ),
+    info(Action::CopyBody, "copy-body", "Copy response body", true),
+    info(
+        Action::CopyHeaders,
+        "copy-headers",
+        "Copy response headers",
+        true,
+    ),
+    info(Action::CopyUrl, "copy-url", "Copy URL", true),
+    info(Action::CopyCurl, "copy-curl", "Copy as curl", true),
+    info(Action::CopyNode, "copy-node", "Copy JSON node", false),
+];
+
+impl Action {
+    pub fn info(&self) -> &'static ActionInfo {
+        ACTIONS.iter().find(|info| info.action == *self).unwrap()
+    }
+}
+
+impl fmt::Display for Action {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.info().name)
+    }
+}
+
+impl FromStr for Action {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Action, String> {
+        ACTIONS
+            .iter()
+            .find(|info| info.name == s)
+            .map(|info| info.action)
+            .ok_or_else(|| format!("unknown action: {}", s))
+    }
+}
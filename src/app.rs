@@ -1,24 +1,76 @@
+mod actions;
+mod help;
+mod json_text;
+mod palette;
+mod response_view;
+mod status_bar;
 mod tab_select;
 mod text_entry;
+mod waterfall;
+mod workflow_view;
 
-use crate::{ui_graph, ui_graph::pane_identifiers::*};
-use petgraph::{graph::Graph, visit::EdgeRef};
+use crate::action::{Action, ACTIONS};
+use crate::cli::LaunchArgs;
+use crate::clipboard;
+use crate::config::{self, Config};
+use crate::history::{self, HistoryEntry};
+use crate::keymap::Keymap;
+use crate::layout::{self, pane_identifiers::*, Fit, Move, Side, PANE_COUNT};
+use crate::session::{Session, TabState};
+use crate::theme::Theme;
+use http_request_tool::assertion::{self, AssertionResult};
+use http_request_tool::collection::{Collection, COLLECTION_FILE};
+use http_request_tool::curl;
+use http_request_tool::executor::{self, Exchange};
+use http_request_tool::extraction;
+use http_request_tool::request::RequestSpec;
+use http_request_tool::runner::Undefined;
+use http_request_tool::timing;
+use http_request_tool::variables::{Environment, Variables};
+use http_request_tool::workflow::{self, WorkflowGraph};
+use reqwest::Method;
 use serde_json::Value;
+use std::cmp::Ordering;
 use std::error::Error;
+use std::fs;
 use std::io::Stdout;
+use std::path::Path;
+use std::sync::{mpsc, Arc};
+use std::thread;
+use std::time::{Duration, Instant};
 use tui::{
     backend::CrosstermBackend,
-    layout::{Constraint, Direction, Layout},
-    style::{Color, Style},
+    layout::{Constraint, Direction, Layout, Margin, Rect},
+    style::{Color, Modifier, Style},
     symbols,
-    text::Spans,
-    widgets::{Block, Borders, Paragraph, Tabs},
+    text::{Span, Spans},
+    widgets::{Block, Borders, Clear, Paragraph, Tabs, Wrap},
     Terminal,
 };
+use unicode_width::UnicodeWidthStr;
 
-use self::{tab_select::TabSelect, text_entry::TextEntry};
+pub use self::text_entry::Motion;
+use self::{
+    help::HelpPage,
+    palette::{Palette, PaletteKind},
+    response_view::ResponseView,
+    tab_select::TabSelect,
+    text_entry::TextEntry,
+    workflow_view::{ActiveRun, RunEvent, WorkflowView},
+};
+
+// How often the session is saved, if it has changed
+const AUTOSAVE_INTERVAL: Duration = Duration::from_secs(5);
+
+// How a pane's border is drawn
+#[derive(Clone, Copy, PartialEq)]
+enum PaneState {
+    Idle,
+    Focused,
+    Editing,
+}
 
-#[derive(PartialEq)]
+#[derive(Clone, Copy, PartialEq)]
 pub enum InputMode {
     Navigation,
     TabSelect,
@@ -27,61 +79,296 @@ pub enum InputMode {
     BodyHeaderSelect,
     MethodSelect,
     ResponseSelect,
+    ResponseView,
+    Workflow,
+    Palette,
+}
+
+// Every mode, in the order the key help lists them
+const MODES: [InputMode; 10] = [
+    InputMode::Navigation,
+    InputMode::TabSelect,
+    InputMode::Entry,
+    InputMode::EndpointEntry,
+    InputMode::BodyHeaderSelect,
+    InputMode::MethodSelect,
+    InputMode::ResponseSelect,
+    InputMode::ResponseView,
+    InputMode::Workflow,
+    InputMode::Palette,
+];
+
+impl InputMode {
+    // The keymap context for the mode
+    pub fn context(&self) -> &'static str {
+        match self {
+            InputMode::Navigation => "navigation",
+            InputMode::TabSelect => "tabs",
+            InputMode::Entry => "editor",
+            InputMode::EndpointEntry => "endpoint",
+            InputMode::BodyHeaderSelect => "request-tabs",
+            InputMode::MethodSelect => "method",
+            InputMode::ResponseSelect => "response-tabs",
+            InputMode::ResponseView => "response",
+            InputMode::Workflow => "workflows",
+            InputMode::Palette => "palette",
+        }
+    }
+
+    // What the status bar calls the mode
+    pub fn name(&self) -> &'static str {
+        match self {
+            InputMode::Navigation => "MOVE",
+            InputMode::TabSelect => "TABS",
+            InputMode::Entry => "EDIT",
+            InputMode::EndpointEntry => "EDIT URL",
+            InputMode::BodyHeaderSelect => "REQUEST TAB",
+            InputMode::MethodSelect => "METHOD",
+            InputMode::ResponseSelect => "RESPONSE TAB",
+            InputMode::ResponseView => "RESPONSE",
+            InputMode::Workflow => "WORKFLOWS",
+            InputMode::Palette => "PALETTE",
+        }
+    }
+
+    // When the mode is used, for the key help
+    fn description(&self) -> &'static str {
+        match self {
+            InputMode::Navigation => "moving between panes",
+            InputMode::TabSelect => "choosing a tab",
+            InputMode::Entry => "editing the request",
+            InputMode::EndpointEntry => "editing the URL",
+            InputMode::BodyHeaderSelect => "choosing BODY, HEADER, QUERY, TESTS or CAPTURE",
+            InputMode::MethodSelect => "choosing the method",
+            InputMode::ResponseSelect => "choosing BODY, HEADER or TESTS",
+            InputMode::ResponseView => "reading the response",
+            InputMode::Workflow => "running workflows",
+            InputMode::Palette => "in the command palette",
+        }
+    }
+}
+
+// How the last request went, or that it's still on its way
+#[derive(Clone, Copy, PartialEq)]
+enum RequestState {
+    Idle,
+    Sending,
+    Failed,
+}
+
+// A request sent on another thread, so the UI keeps drawing while it waits
+struct PendingRequest {
+    // The tab it was sent from, which gets the response
+    tab: usize,
+    started: Instant,
+    // Test and capture lines that didn't parse, reported with the results
+    invalid: Vec<AssertionResult>,
+    // Variables it uses that have no value, which were sent as written
+    undefined: Vec<String>,
+    // The exchange, and the values it captured into the environment and
+    // runtime variables
+    rx: mpsc::Receiver<(Result<Exchange, String>, Variables, Variables)>,
 }
 
 pub struct App {
-    tabs: Vec<usize>, // This vector holds IDs, not names
+    tabs: Vec<TabState>,
     current_tab: usize,
     pub input_mode: InputMode,
     response_body: String,
-    current_pane: petgraph::graph::NodeIndex,
-    ui: Graph<usize, usize>,
+    history: Vec<HistoryEntry>,
+    current_pane: usize,
     endpoint_widget: TextEntry,
+    request_tabs_widget: TabSelect,
     request_widget: TextEntry,
+    header_widget: TextEntry,
+    query_widget: TextEntry,
+    tests_widget: TextEntry,
+    capture_widget: TextEntry,
     method_select_widget: TabSelect,
-    widget_styles: [Color; 8],
+    response_tabs_widget: TabSelect,
+    response_view: ResponseView,
+    test_results: Vec<AssertionResult>,
+    environment: Environment,
+    runtime_variables: Variables,
+    collection: Option<Collection>,
+    workflow_view: WorkflowView,
+    clipboard: String,
+    // Shown in the status bar, e.g. after copying
+    notice: String,
+    request_state: RequestState,
+    pending: Option<PendingRequest>,
+    pane_states: [PaneState; PANE_COUNT],
+    // Where each pane was last drawn, for the mouse and moving between panes
+    pane_areas: [Rect; PANE_COUNT],
+    layout: layout::Layout,
+    // How the layout was last changed to fit the terminal
+    fit: Fit,
+    pub keymap: Keymap,
+    help: Option<HelpPage>,
+    palette: Option<Palette>,
+    // The mode to go back to when the palette closes
+    palette_return: InputMode,
+    // Variables of the collection request that was last opened
+    item_variables: Variables,
+    config: Config,
+    theme: Theme,
+    // The session as it was last saved, and when it was last checked
+    saved_session: String,
+    last_autosave: Instant,
 }
 
 impl App {
     pub fn new() -> App {
-        let graph = ui_graph::init_ui_graph();
-        let tabs_pane = graph.node_indices().find(|node| graph[*node] == 0).unwrap();
-        let mut widget_styles: [Color; 8] = [Color::Rgb(255, 255, 255); 8];
-        widget_styles[0] = Color::Yellow;
-        App {
-            tabs: vec![0],
+        let mut pane_states = [PaneState::Idle; PANE_COUNT];
+        pane_states[PANE_TABS] = PaneState::Focused;
+
+        // Load the collection in the current directory, if there is one
+        let mut response_body = String::new();
+        let collection = if Path::new(COLLECTION_FILE).exists() {
+            match Collection::load(Path::new(COLLECTION_FILE)) {
+                Ok(collection) => Some(collection),
+                Err(e) => {
+                    response_body = e.to_string();
+                    None
+                }
+            }
+        } else {
+            None
+        };
+        let keymap = Keymap::load().unwrap_or_else(|e| {
+            response_body = format!("{}\n{}", e, response_body);
+            Keymap::default()
+        });
+        let config = Config::load().unwrap_or_else(|e| {
+            response_body = format!("{}\n{}", e, response_body);
+            Config::default()
+        });
+        let theme_name = config.theme.as_deref().unwrap_or("dark");
+        let theme = Theme::named(theme_name, &config).unwrap_or_else(|e| {
+            response_body = format!("{}\n{}", e, response_body);
+            Theme::named("dark", &config).unwrap()
+        });
+        let layout = layout::Layout::load().unwrap_or_else(|e| {
+            response_body = format!("{}\n{}", e, response_body);
+            layout::Layout::default()
+        });
+        let workflow_names = collection
+            .iter()
+            .flat_map(|collection| &collection.workflows)
+            .map(|workflow| workflow.name.clone())
+            .collect();
+
+        let mut app = App {
+            tabs: vec![TabState::default()],
             current_tab: 0,
             input_mode: InputMode::Navigation,
-            response_body: String::new(),
-            current_pane: tabs_pane,
-            ui: graph,
+            response_body,
+            history: Vec::new(),
+            current_pane: PANE_TABS,
             endpoint_widget: TextEntry::new("http://".to_string(), false),
+            request_tabs_widget: TabSelect::new(
+                vec![
+                    "BODY".to_string(),
+                    "HEADER".to_string(),
+                    "QUERY".to_string(),
+                    "TESTS".to_string(),
+                    "CAPTURE".to_string(),
+                ],
+                "".to_string(),
+            ),
             request_widget: TextEntry::new("".to_string(), true),
+            header_widget: TextEntry::new("".to_string(), true),
+            query_widget: TextEntry::new("".to_string(), true),
+            tests_widget: TextEntry::new("".to_string(), true),
+            capture_widget: TextEntry::new("".to_string(), true),
             method_select_widget: TabSelect::new(
                 vec![
                     "GET".to_string(),
                     "POST".to_string(),
                     "PUT".to_string(),
+                    "PATCH".to_string(),
                     "DELETE".to_string(),
+                    "HEAD".to_string(),
+                    "OPTIONS".to_string(),
                 ],
                 "".to_string(),
-                Color::Magenta,
             ),
-            widget_styles,
+            response_tabs_widget: TabSelect::new(
+                vec![
+                    "BODY".to_string(),
+                    "HEADER".to_string(),
+                    "TESTS".to_string(),
+                ],
+                "".to_string(),
+            ),
+            response_view: ResponseView::new(),
+            test_results: Vec::new(),
+            environment: Environment::default(),
+            runtime_variables: Variables::new(),
+            collection,
+            workflow_view: WorkflowView::new(workflow_names),
+            clipboard: String::new(),
+            notice: String::new(),
+            request_state: RequestState::Idle,
+            pending: None,
+            pane_states,
+            pane_areas: [Rect::default(); PANE_COUNT],
+            layout,
+            fit: Fit::Full,
+            keymap,
+            help: None,
+            palette: None,
+            palette_return: InputMode::Navigation,
+            item_variables: Variables::new(),
+            config,
+            theme,
+            saved_session: String::new(),
+            last_autosave: Instant::now(),
+        };
+        match Session::load() {
+            Ok(Some(session)) => app.restore(session),
+            Ok(None) => {}
+            Err(e) => app.response_body = format!("{}\n{}", e, app.response_body),
+        }
+        if config::first_run() {
+            app.open_welcome();
         }
+        app
     }
 
     pub fn draw(
-        &self,
+        &mut self,
         terminal: &mut Terminal<CrosstermBackend<Stdout>>,
     ) -> Result<(), Box<dyn Error>> {
         terminal.draw(|frame| {
             let size = frame.size();
-            let chunks = Layout::default()
-                .direction(Direction::Vertical)
-                .margin(1)
-                .constraints([Constraint::Length(3), Constraint::Min(2)].as_ref())
-                .split(size);
+            self.fit = self.config.responsive.fit(&self.layout, size);
+            if self.fit == Fit::TooSmall {
+                let message = format!(
+                    "Terminal too small: {}x{}, needs at least {}x{}",
+                    size.width,
+                    size.height,
+                    self.config.responsive.min_width,
+                    self.config.responsive.min_height
+                );
+                frame.render_widget(
+                    Paragraph::new(message)
+                        .style(Style::default().fg(self.theme.error))
+                        .wrap(Wrap { trim: true }),
+                    size,
+                );
+                self.pane_areas = [Rect::default(); PANE_COUNT];
+                return;
+            }
+            // A single pane shows the side with the focus
+            let areas = self
+                .layout
+                .fitted(self.fit, layout::side(self.current_pane))
+                .tree(!self.history.is_empty())
+                .areas(size.inner(&Margin {
+                    vertical: 1,
+                    horizontal: 1,
+                }));
 
             // TODO: Implement tab selection
             let tab_names = (1..self.tabs.len() + 1)
@@ -91,334 +378,1624 @@ impl App {
                 .block(
                     Block::default()
                         .title("Tabs")
-                        .style(Style::default().fg(self.widget_styles[PANE_TABS]))
+                        .style(Style::default().fg(self.pane_colour(PANE_TABS)))
                         .borders(Borders::ALL),
                 )
                 .select(self.current_tab)
-                .highlight_style(Style::default().fg(Color::Magenta))
+                .highlight_style(Style::default().fg(self.theme.highlight))
                 .divider(symbols::line::VERTICAL);
 
-            // Body layout
-            let body_layout = Layout::default()
-                .direction(Direction::Horizontal)
-                .constraints([Constraint::Ratio(1, 2), Constraint::Ratio(1, 2)])
-                .split(chunks[1]);
+            // The response is scrolled first, before the widgets below
+            // borrow the app
+            let response_line_widths = self.response_line_widths();
+            self.response_view
+                .scroll_to_cursor(areas.panes[PANE_RESPONSE_TEXT], &response_line_widths);
 
             // ===== REQUEST BLOCK LAYOUT =====
 
-            let request_layout = Layout::default()
+            // JSON bodies get a status line under the editor
+            let body_json = self.body_is_json();
+            self.request_widget.check_json(body_json);
+            let show_json_status =
+                body_json && self.request_tabs_widget.get_current_tab() == "BODY";
+            let entry_layout = Layout::default()
                 .direction(Direction::Vertical)
                 .constraints([
-                    Constraint::Length(3),
-                    Constraint::Length(3),
                     Constraint::Min(2),
-                    Constraint::Length(3),
+                    Constraint::Length(show_json_status as u16),
                 ])
-                .split(body_layout[0]);
-
-            let endpoint_entry = self
-                .endpoint_widget
-                .get_widget(self.widget_styles[PANE_ENDPOINT]);
-
-            let body_header_options = vec![
-                Spans::from("BODY"),
-                Spans::from("HEADER"),
-                Spans::from("QUERY"),
-            ];
-            let body_header_select = Tabs::new(body_header_options)
-                .block(
-                    Block::default()
-                        .borders(Borders::ALL)
-                        .style(Style::default().fg(self.widget_styles[PANE_BODY_HEADER_SELECT])),
-                )
-                .divider(symbols::line::VERTICAL);
+                .split(areas.panes[PANE_REQUEST_ENTRY]);
 
-            // TODO: Separate widget for body/header/query
-            let request_entry = self
-                .request_widget
-                .get_widget(self.widget_styles[PANE_REQUEST_ENTRY]);
+            self.endpoint_widget
+                .scroll_to_cursor(areas.panes[PANE_ENDPOINT]);
+            self.request_tabs_widget
+                .scroll_to_current(areas.panes[PANE_BODY_HEADER_SELECT]);
+            self.method_select_widget
+                .scroll_to_current(areas.panes[PANE_METHOD_SELECT]);
+            self.response_tabs_widget
+                .scroll_to_current(areas.panes[PANE_RESPONSE_TABS]);
+            self.entry_widget_mut().scroll_to_cursor(entry_layout[0]);
 
-            let request_bottom_layout = Layout::default()
-                .direction(Direction::Horizontal)
-                .constraints([Constraint::Min(1), Constraint::Length(6)])
-                .split(request_layout[3]);
+            let endpoint_entry = self.endpoint_widget.get_widget(
+                self.pane_colour(PANE_ENDPOINT),
+                areas.panes[PANE_ENDPOINT],
+                &self.theme,
+            );
+
+            let body_header_select = self.request_tabs_widget.get_widget(
+                self.pane_colour(PANE_BODY_HEADER_SELECT),
+                self.theme.highlight,
+            );
+
+            let request_entry = self.entry_widget().get_widget(
+                self.pane_colour(PANE_REQUEST_ENTRY),
+                entry_layout[0],
+                &self.theme,
+            );
+
+            let json_status = match self.request_widget.json_error() {
+                Some(e) => Spans::from(Span::styled(
+                    format!("✗ {}", e),
+                    Style::default().fg(self.theme.error),
+                )),
+                None => {
+                    let hints = self.hint_text(
+                        "editor",
+                        &[
+                            (&[Action::FormatJson], "format"),
+                            (&[Action::MinifyJson], "minify"),
+                        ],
+                        " · ",
+                    );
+                    let text = match hints.as_str() {
+                        "" => "✓ JSON".to_string(),
+                        hints => format!("✓ JSON · {}", hints),
+                    };
+                    Spans::from(Span::styled(text, Style::default().fg(self.theme.dim)))
+                }
+            };
 
             let method_select = self
                 .method_select_widget
-                .get_widget(self.widget_styles[PANE_METHOD_SELECT]);
+                .get_widget(self.pane_colour(PANE_METHOD_SELECT), self.theme.highlight);
 
             let send_button = Paragraph::new(Spans::from("SEND")).block(
                 Block::default()
-                    .style(Style::default().fg(self.widget_styles[PANE_SEND_BUTTON]))
+                    .style(Style::default().fg(self.pane_colour(PANE_SEND_BUTTON)))
                     .borders(Borders::ALL),
             );
 
             // ===== RESPONSE BLOCK LAYOUT =====
 
-            let response_layout = Layout::default()
-                .direction(Direction::Vertical)
-                .constraints([Constraint::Length(3), Constraint::Min(1)])
-                .split(body_layout[1]);
+            let last_entry = self.history.last();
 
-            let response_tabs_names = vec![Spans::from("BODY"), Spans::from("HEADER")];
-            let response_tabs = Tabs::new(response_tabs_names)
-                .block(Block::default().borders(Borders::ALL))
-                .style(Style::default().fg(self.widget_styles[PANE_RESPONSE_TABS]))
-                .divider(symbols::line::VERTICAL);
+            let response_tabs = self
+                .response_tabs_widget
+                .get_widget(self.pane_colour(PANE_RESPONSE_TABS), self.theme.highlight);
 
-            let response_lines: Vec<Spans> = self
-                .response_body
-                .split('\n')
-                .collect::<Vec<&str>>()
-                .iter()
-                .map(|s| Spans::from(s.clone()))
-                .collect();
-            let response_paragraph = Paragraph::new(response_lines).block(
-                Block::default()
-                    .borders(Borders::ALL)
-                    .style(Style::default().fg(self.widget_styles[PANE_RESPONSE_TEXT])),
+            let response_body = self.displayed_body();
+            let response_lines: Vec<Spans> =
+                match self.response_tabs_widget.get_current_tab().as_str() {
+                    "HEADER" => last_entry
+                        .map(|entry| {
+                            entry
+                                .response
+                                .headers
+                                .iter()
+                                .map(|(name, value)| Spans::from(format!("{}: {}", name, value)))
+                                .collect()
+                        })
+                        .unwrap_or_default(),
+                    "TESTS" => self
+                        .test_results
+                        .iter()
+                        .map(|result| {
+                            let (mark, color) = if result.passed {
+                                ("✓", self.theme.success)
+                            } else {
+                                ("✗", self.theme.error)
+                            };
+                            Spans::from(vec![
+                                Span::styled(
+                                    format!("{} {}", mark, result.description),
+                                    Style::default().fg(color),
+                                ),
+                                Span::raw(format!(" ({})", result.message)),
+                            ])
+                        })
+                        .collect(),
+                    _ if self.response_is_json() => {
+                        json_text::highlight(&response_body, &self.theme)
+                    }
+                    _ => response_body
+                        .split('\n')
+                        .collect::<Vec<&str>>()
+                        .iter()
+                        .map(|s| Spans::from(*s))
+                        .collect(),
+                };
+            let mut response_title = vec![];
+            if let Some(entry) = last_entry {
+                response_title.push(Span::styled(
+                    format!("Status: {}", entry.response.status),
+                    Style::default().fg(self.theme.status(entry.response.status)),
+                ));
+            }
+            let cursor_colour = match self.input_mode {
+                InputMode::ResponseView => Some(self.theme.cursor_line),
+                _ => None,
+            };
+            let response_paragraph = self.response_view.get_widget(
+                response_lines,
+                Spans::from(response_title),
+                self.pane_colour(PANE_RESPONSE_TEXT),
+                cursor_colour,
             );
 
             // ===== CURSOR DRAWING =====
 
             match self.input_mode {
                 InputMode::Entry => {
-                    let (x, y) = self.request_widget.get_cursor_xy();
-                    frame.set_cursor(request_layout[2].x + x + 1, request_layout[2].y + y + 1)
+                    let (x, y) = self.entry_widget().get_cursor_xy();
+                    frame.set_cursor(entry_layout[0].x + x + 1, entry_layout[0].y + y + 1)
                 }
                 InputMode::EndpointEntry => {
                     let (x, _) = self.endpoint_widget.get_cursor_xy();
-                    frame.set_cursor(request_layout[0].x + x + 1, request_layout[0].y + 1)
+                    let area = areas.panes[PANE_ENDPOINT];
+                    frame.set_cursor(area.x + x + 1, area.y + 1)
                 }
                 _ => {}
             }
 
-            frame.render_widget(tabs, chunks[0]);
-            frame.render_widget(response_tabs, response_layout[0]);
-            frame.render_widget(response_paragraph, response_layout[1]);
-            frame.render_widget(endpoint_entry, request_layout[0]);
-            frame.render_widget(body_header_select, request_layout[1]);
-            frame.render_widget(method_select, request_bottom_layout[0]);
-            frame.render_widget(send_button, request_bottom_layout[1]);
-            frame.render_widget(request_entry, request_layout[2]);
+            frame.render_widget(tabs, areas.panes[PANE_TABS]);
+            frame.render_widget(response_tabs, areas.panes[PANE_RESPONSE_TABS]);
+            frame.render_widget(response_paragraph, areas.panes[PANE_RESPONSE_TEXT]);
+            if let Some(entry) = last_entry.filter(|_| areas.timing.area() > 0) {
+                let waterfall = waterfall::get_widget(
+                    &entry.response.timings,
+                    format!("Timing: {} {}", entry.request.method, entry.request.url),
+                    areas.timing.width.saturating_sub(2),
+                    self.pane_colour(PANE_RESPONSE_TEXT),
+                );
+                frame.render_widget(waterfall, areas.timing);
+            }
+            frame.render_widget(endpoint_entry, areas.panes[PANE_ENDPOINT]);
+            frame.render_widget(body_header_select, areas.panes[PANE_BODY_HEADER_SELECT]);
+            frame.render_widget(method_select, areas.panes[PANE_METHOD_SELECT]);
+            frame.render_widget(send_button, areas.panes[PANE_SEND_BUTTON]);
+            frame.render_widget(request_entry, entry_layout[0]);
+            if show_json_status {
+                frame.render_widget(Paragraph::new(json_status), entry_layout[1]);
+            }
+
+            let hints = self.key_hints();
+            let status_bar = status_bar::get_widget(
+                self.input_mode.name(),
+                self.status_spans(),
+                &hints,
+                areas.status.width,
+                &self.theme,
+            );
+            frame.render_widget(status_bar, areas.status);
+
+            self.pane_areas = areas.panes;
+            self.pane_areas[PANE_REQUEST_ENTRY] = entry_layout[0];
+            self.focus_visible_pane();
+
+            if self.workflow_view.visible {
+                let area = centered_rect(size, 80, 80);
+                frame.render_widget(Clear, area);
+                let workflow_hints = self.hint_text(
+                    "workflows",
+                    &[
+                        (&[Action::Up, Action::Down], "choose"),
+                        (&[Action::Enter], "run"),
+                        (&[Action::Exit], "close"),
+                    ],
+                    ", ",
+                );
+                let workflows =
+                    self.workflow_view
+                        .get_widget(&workflow_hints, self.theme.editing, &self.theme);
+                frame.render_widget(workflows, area);
+            }
+
+            if let Some(help) = self.help.as_mut() {
+                let area = centered_rect(size, 80, 80);
+                help.fit(area);
+                frame.render_widget(Clear, area);
+                frame.render_widget(help.get_widget(self.theme.editing), area);
+            }
+
+            if let Some(palette) = &self.palette {
+                let area = centered_rect(size, 60, 60);
+                let (x, y) = palette.cursor_xy(area);
+                frame.render_widget(Clear, area);
+                frame.render_widget(
+                    palette.get_widget(self.theme.editing, area, &self.theme),
+                    area,
+                );
+                frame.set_cursor(area.x + x + 1, area.y + y + 1);
+            }
         })?;
         Ok(())
     }
 
     // Tab navigation
     pub fn tab_left(&mut self) {
-        self.current_tab -= (self.current_tab > 0) as usize;
+        if self.current_tab > 0 {
+            self.switch_tab(self.current_tab - 1);
+        }
     }
     pub fn tab_right(&mut self) {
-        self.current_tab += 1;
-        if self.current_tab >= self.tabs.len() {
-            let mut i: usize = 0;
-            loop {
-                if !self.tabs.contains(&i) {
-                    self.tabs.push(i);
-                    break;
-                }
-                i += 1;
-            }
+        if self.current_tab + 1 >= self.tabs.len() {
+            self.new_tab();
+        } else {
+            self.switch_tab(self.current_tab + 1);
         }
     }
+    pub fn new_tab(&mut self) {
+        self.save_tab();
+        self.tabs.push(TabState::default());
+        self.load_tab(self.tabs.len() - 1);
+    }
     pub fn tab_delete(&mut self) {
+        // A request on its way from the tab is forgotten
+        if let Some(pending) = self.pending.as_mut() {
+            match pending.tab.cmp(&self.current_tab) {
+                Ordering::Equal => {
+                    self.pending = None;
+                    self.request_state = RequestState::Idle;
+                }
+                Ordering::Greater => pending.tab -= 1,
+                Ordering::Less => {}
+            }
+        }
         self.tabs.remove(self.current_tab);
         if self.tabs.is_empty() {
-            self.tabs.push(0);
-        }
-        if self.current_tab >= self.tabs.len() {
-            self.current_tab -= 1;
+            self.tabs.push(TabState::default());
         }
+        self.load_tab(self.current_tab.min(self.tabs.len() - 1));
+    }
+    fn switch_tab(&mut self, tab: usize) {
+        self.save_tab();
+        self.load_tab(tab);
     }
 
-    // Cleanly exit
-    pub fn exit(&mut self) {}
-}
+    // The editors and response belong to the current tab. Its entry in
+    // `tabs` is only brought up to date when it's left or saved.
+    fn save_tab(&mut self) {
+        self.tabs[self.current_tab] = TabState {
+            endpoint: self.endpoint_widget.get_text(),
+            method: self.method_select_widget.get_current_tab(),
+            headers: self.header_widget.get_text(),
+            query: self.query_widget.get_text(),
+            body: self.request_widget.get_text(),
+            tests: self.tests_widget.get_text(),
+            capture: self.capture_widget.get_text(),
+            request_tab: self.request_tabs_widget.get_current_tab(),
+            response_tab: self.response_tabs_widget.get_current_tab(),
+            response_body: self.response_body.clone(),
+            test_results: self.test_results.clone(),
+            history: self.history.clone(),
+            variables: self.item_variables.clone(),
+        };
+    }
 
-// Navigation functions
-impl App {
-    pub fn escape(&mut self) {
-        let edges = self.ui.edges(self.current_pane);
-        for edge in edges {
-            if *edge.weight() == 6 {
-                self.widget_styles[self.ui[self.current_pane]] = Color::Rgb(255, 255, 255);
-                self.current_pane = edge.target();
-                self.widget_styles[self.ui[self.current_pane]] = Color::Yellow;
-            }
+    fn load_tab(&mut self, tab: usize) {
+        self.current_tab = tab;
+        let state = self.tabs[tab].clone();
+        self.endpoint_widget.replace_text(state.endpoint);
+        self.method_select_widget.select_or_add(&state.method);
+        self.header_widget.replace_text(state.headers);
+        self.query_widget.replace_text(state.query);
+        self.request_widget.replace_text(state.body);
+        self.tests_widget.replace_text(state.tests);
+        self.capture_widget.replace_text(state.capture);
+        self.request_tabs_widget.select(&state.request_tab);
+        self.response_tabs_widget.select(&state.response_tab);
+        self.response_body = state.response_body;
+        self.test_results = state.test_results;
+        self.history = state.history;
+        self.item_variables = state.variables;
+        self.response_view.reset();
+    }
+
+    // Cleanly exit, saving the session for next time
+    pub fn exit(&mut self) -> Result<(), Box<dyn Error>> {
+        self.save_session()
+    }
+
+    fn pane_colour(&self, pane: usize) -> Color {
+        match self.pane_states[pane] {
+            PaneState::Idle => self.theme.idle,
+            PaneState::Focused => self.theme.focused,
+            PaneState::Editing => self.theme.editing,
         }
     }
+}
 
+// Navigation functions
+impl App {
     pub fn left(&mut self) {
-        let edges = self.ui.edges(self.current_pane);
-        for edge in edges {
-            if *edge.weight() == 1 {
-                self.widget_styles[self.ui[self.current_pane]] = Color::Rgb(255, 255, 255);
-                self.current_pane = edge.target();
-                self.widget_styles[self.ui[self.current_pane]] = Color::Yellow;
-            }
-        }
+        self.move_focus(Move::Left);
     }
 
     pub fn right(&mut self) {
-        let edges = self.ui.edges(self.current_pane);
-        for edge in edges {
-            if *edge.weight() == 2 {
-                self.widget_styles[self.ui[self.current_pane]] = Color::Rgb(255, 255, 255);
-                self.current_pane = edge.target();
-                self.widget_styles[self.ui[self.current_pane]] = Color::Yellow;
-            }
-        }
+        self.move_focus(Move::Right);
     }
 
     pub fn up(&mut self) {
-        let edges = self.ui.edges(self.current_pane);
-        for edge in edges {
-            if *edge.weight() == 3 {
-                self.widget_styles[self.ui[self.current_pane]] = Color::Rgb(255, 255, 255);
-                self.current_pane = edge.target();
-                self.widget_styles[self.ui[self.current_pane]] = Color::Yellow;
-            }
-        }
+        self.move_focus(Move::Up);
     }
 
     pub fn down(&mut self) {
-        let edges = self.ui.edges(self.current_pane);
-        for edge in edges {
-            if *edge.weight() == 4 {
-                self.widget_styles[self.ui[self.current_pane]] = Color::Rgb(255, 255, 255);
-                self.current_pane = edge.target();
-                self.widget_styles[self.ui[self.current_pane]] = Color::Yellow;
-            }
-        }
+        self.move_focus(Move::Down);
     }
 
-    pub fn enter(&mut self) {
-        let edges = self.ui.edges(self.current_pane);
-        for edge in edges {
-            if *edge.weight() == 5 {
-                self.widget_styles[self.ui[self.current_pane]] = Color::Rgb(255, 255, 255);
-                self.current_pane = edge.target();
-                self.widget_styles[self.ui[self.current_pane]] = Color::Yellow;
-                return;
-            }
+    // Panes are moved between by where they are on screen, so moving keeps
+    // working however the layout is changed
+    fn move_focus(&mut self, direction: Move) {
+        if let Some(pane) = layout::neighbour(&self.pane_areas, self.current_pane, direction) {
+            self.focus(pane);
         }
+    }
 
+    pub fn enter(&mut self) {
         // Special actions for certain widgets
-        match self.ui[self.current_pane] {
+        match self.current_pane {
             PANE_TABS => {
                 self.input_mode = InputMode::TabSelect;
-                self.widget_styles[self.ui[self.current_pane]] = Color::Red;
+                self.pane_states[self.current_pane] = PaneState::Editing;
             }
             PANE_ENDPOINT => {
                 self.input_mode = InputMode::EndpointEntry;
-                self.widget_styles[self.ui[self.current_pane]] = Color::Red;
+                self.pane_states[self.current_pane] = PaneState::Editing;
             }
             PANE_BODY_HEADER_SELECT => {
                 self.input_mode = InputMode::BodyHeaderSelect;
-                self.widget_styles[self.ui[self.current_pane]] = Color::Red;
+                self.pane_states[self.current_pane] = PaneState::Editing;
             }
             PANE_REQUEST_ENTRY => {
                 self.input_mode = InputMode::Entry;
-                self.widget_styles[self.ui[self.current_pane]] = Color::Red;
-            }
-            PANE_SEND_BUTTON => {
-                self.send_request().unwrap();
+                self.pane_states[self.current_pane] = PaneState::Editing;
             }
+            PANE_SEND_BUTTON => self.send(),
             PANE_METHOD_SELECT => {
                 self.input_mode = InputMode::MethodSelect;
-                self.widget_styles[self.ui[self.current_pane]] = Color::Red;
+                self.pane_states[self.current_pane] = PaneState::Editing;
             }
             PANE_RESPONSE_TABS => {
                 self.input_mode = InputMode::ResponseSelect;
-                self.widget_styles[self.ui[self.current_pane]] = Color::Red;
+                self.pane_states[self.current_pane] = PaneState::Editing;
+            }
+            PANE_RESPONSE_TEXT => {
+                self.input_mode = InputMode::ResponseView;
+                self.pane_states[self.current_pane] = PaneState::Editing;
             }
             _ => {}
         }
     }
 
-    fn send_request(&mut self) -> Result<(), Box<dyn Error>> {
-        let client = reqwest::blocking::Client::new();
-
-        let body_string = self.request_widget.get_text();
-        let body_json: Value = serde_json::from_str(&body_string)?;
-
-        let method = self.method_select_widget.get_current_tab();
-        let res = match method.as_str() {
-            "GET" => client
-                .get(self.endpoint_widget.get_text())
-                .json(&body_json)
-                .send()?
-                .text()?,
-            "POST" => client
-                .post(self.endpoint_widget.get_text())
-                .json(&body_json)
-                .send()?
-                .text()?,
-            "PUT" => client
-                .put(self.endpoint_widget.get_text())
-                .json(&body_json)
-                .send()?
-                .text()?,
-            "DELETE" => client
-                .delete(self.endpoint_widget.get_text())
-                .json(&body_json)
-                .send()?
-                .text()?,
-            _ => "".to_string(),
+    // Marks the request to be sent, once "Sending" is on screen
+    pub fn send(&mut self) {
+        if self.pending.is_some() {
+            self.notice = "A request is already on its way".to_string();
+            return;
+        }
+        match self.send_request() {
+            Ok(pending) => {
+                self.pending = Some(pending);
+                self.request_state = RequestState::Sending;
+            }
+            Err(e) => {
+                self.response_body = format!("Error: {}", e);
+                self.request_state = RequestState::Failed;
+            }
+        }
+    }
+
+    fn send_request(&mut self) -> Result<PendingRequest, Box<dyn Error>> {
+        // Lines that don't parse are reported as failed tests
+        let mut invalid = vec![];
+        let assertions = parse_entry_lines(
+            assertion::parse_lines(&self.tests_widget.get_text()),
+            "invalid assertion",
+            &mut invalid,
+        );
+        let extract = parse_entry_lines(
+            extraction::parse_lines(&self.capture_widget.get_text()),
+            "invalid extraction",
+            &mut invalid,
+        );
+        let (request, undefined) = self.build_request(assertions, extract)?;
+        let probe = self.config.connection_timings;
+
+        let (tx, rx) = mpsc::channel();
+        thread::spawn(move || {
+            // The probe is best-effort: without it there are no connection
+            // timings
+            let connection = match request.full_url() {
+                Ok(url) if probe => timing::probe(&url).ok(),
+                _ => None,
+            };
+            let mut environment = Variables::new();
+            let mut runtime = Variables::new();
+            let exchange = executor::send(request, &mut environment, &mut runtime)
+                .map(|mut exchange| {
+                    exchange.response.timings.connection = connection;
+                    exchange
+                })
+                .map_err(|e| e.to_string());
+            let _ = tx.send((exchange, environment, runtime));
+        });
+        Ok(PendingRequest {
+            tab: self.current_tab,
+            started: Instant::now(),
+            invalid,
+            undefined,
+            rx,
+        })
+    }
+
+    // Shows the response once it has arrived, in the tab that sent it
+    fn receive_response(&mut self) {
+        let received = match self.pending.as_ref().map(|pending| pending.rx.try_recv()) {
+            Some(Ok(received)) => received,
+            Some(Err(mpsc::TryRecvError::Disconnected)) => (
+                Err("the request was lost (see crash.log)".to_string()),
+                Variables::new(),
+                Variables::new(),
+            ),
+            _ => return,
         };
+        let pending = self.pending.take().unwrap();
+        let (exchange, environment, runtime) = received;
+        self.environment.variables.extend(environment);
+        self.runtime_variables.extend(runtime);
 
-        self.response_body = res;
-        Ok(())
+        let (response_body, test_results, entry) = match exchange {
+            Ok(exchange) => {
+                self.request_state = RequestState::Idle;
+                self.notice = if pending.undefined.is_empty() {
+                    String::new()
+                } else {
+                    format!(
+                        "Undefined variables sent as written: {}",
+                        pending.undefined.join(", ")
+                    )
+                };
+                (
+                    exchange.response.body.clone(),
+                    exchange
+                        .results
+                        .into_iter()
+                        .chain(pending.invalid)
+                        .collect(),
+                    Some(HistoryEntry {
+                        request: exchange.request,
+                        response: exchange.response,
+                    }),
+                )
+            }
+            Err(e) => {
+                self.request_state = RequestState::Failed;
+                (format!("Error: {}", e), self.test_results.clone(), None)
+            }
+        };
+
+        if pending.tab == self.current_tab {
+            self.response_body = response_body;
+            self.test_results = test_results;
+            if let Some(entry) = entry {
+                history::record(&mut self.history, entry);
+            }
+            self.response_view.reset();
+            // With room for only one side, show the response
+            if self.fit == Fit::SinglePane {
+                self.exit_input();
+                self.focus(PANE_RESPONSE_TEXT);
+            }
+        } else if let Some(tab) = self.tabs.get_mut(pending.tab) {
+            tab.response_body = response_body;
+            if entry.is_some() {
+                tab.test_results = test_results;
+            }
+            if let Some(entry) = entry {
+                history::record(&mut tab.history, entry);
+            }
+        }
     }
-}
 
-// Endpoint entry
-impl App {
-    pub fn endpoint_input_char(&mut self, c: char) {
-        self.endpoint_widget.input_char(c);
+    // The request in the editors with its variables filled in, and the names
+    // of any it uses that have no value
+    fn build_request(
+        &self,
+        assertions: Vec<assertion::Assertion>,
+        extract: Vec<extraction::Extraction>,
+    ) -> Result<(RequestSpec, Vec<String>), Box<dyn Error>> {
+        let variables = executor::variables(
+            &self.item_variables,
+            &self.environment.variables,
+            &self.runtime_variables,
+        );
+        let request = RequestSpec {
+            name: String::new(),
+            method: self.method_select_widget.get_current_tab(),
+            url: self.endpoint_widget.get_text(),
+            headers: parse_pairs(&self.header_widget.get_text(), ':'),
+            query: parse_pairs(&self.query_widget.get_text(), '='),
+            body: self.request_widget.get_text(),
+            assertions,
+            extract,
+        };
+        let undefined = request.undefined(&variables);
+        let mut request = request.resolve(&variables);
+
+        let has_content_type = request
+            .headers
+            .iter()
+            .any(|(name, _)| name.eq_ignore_ascii_case("Content-Type"));
+        if !request.body.is_empty() && !has_content_type {
+            serde_json::from_str::<Value>(&request.body)
+                .map_err(|e| format!("invalid JSON body: {}", e))?;
+            request
+                .headers
+                .push(("Content-Type".to_string(), "application/json".to_string()));
+        }
+        Ok((request, undefined))
+    }
+
+    fn response_is_json(&self) -> bool {
+        self.history.last().is_some_and(|entry| {
+            entry
+                .response
+                .header("Content-Type")
+                .is_some_and(|value| value.contains("json"))
+        })
     }
 
-    pub fn endpoint_backspace(&mut self) {
-        self.endpoint_widget.backspace();
+    // The response body as shown, with JSON laid out one value per line
+    fn displayed_body(&self) -> String {
+        if self.response_is_json() && json_text::validate(&self.response_body).is_ok() {
+            json_text::format(&self.response_body)
+        } else {
+            self.response_body.clone()
+        }
     }
 
-    // TODO: Implement left/right cursor movement
+    // The Content-Type from the HEADER tab, lowercased
+    fn body_content_type(&self) -> Option<String> {
+        parse_pairs(&self.header_widget.get_text(), ':')
+            .into_iter()
+            .find(|(name, _)| name.eq_ignore_ascii_case("Content-Type"))
+            .map(|(_, value)| value.to_ascii_lowercase())
+    }
+
+    // Bodies without a Content-Type are sent as JSON
+    fn body_is_json(&self) -> bool {
+        self.body_content_type()
+            .is_none_or(|content_type| content_type.contains("json"))
+    }
+
+    // The text to open in an external editor and the file extension to use
+    pub fn external_edit_text(&self) -> (String, &'static str) {
+        let text = self.entry_widget().get_text();
+        let extension = match self.request_tabs_widget.get_current_tab().as_str() {
+            "BODY" => match self.body_content_type() {
+                Some(t) if t.contains("json") => "json",
+                Some(t) if t.contains("xml") => "xml",
+                Some(t) if t.contains("html") => "html",
+                Some(_) => "txt",
+                None => "json",
+            },
+            _ => "txt",
+        };
+        (text, extension)
+    }
+
+    // Reformats a valid JSON body, either indented or on one line
+    pub fn format_body(&mut self, minify: bool) {
+        if self.request_tabs_widget.get_current_tab() != "BODY"
+            || !self.body_is_json()
+            || json_text::validate(&self.request_widget.get_text()).is_err()
+        {
+            return;
+        }
+        let text = self.request_widget.get_text();
+        let formatted = if minify {
+            json_text::minify(&text)
+        } else {
+            json_text::format(&text)
+        };
+        self.request_widget.set_text(formatted);
+    }
+
+    pub fn finish_external_edit(&mut self, result: Result<String, Box<dyn Error>>) {
+        match result {
+            Ok(text) => self.entry_widget_mut().set_text(text),
+            Err(e) => self.response_body = format!("Editor failed: {}", e),
+        }
+    }
+
+    // The editor below the BODY/HEADER/QUERY/TESTS/CAPTURE tabs
+    fn entry_widget(&self) -> &TextEntry {
+        match self.request_tabs_widget.get_current_tab().as_str() {
+            "HEADER" => &self.header_widget,
+            "QUERY" => &self.query_widget,
+            "TESTS" => &self.tests_widget,
+            "CAPTURE" => &self.capture_widget,
+            _ => &self.request_widget,
+        }
+    }
+
+    fn entry_widget_mut(&mut self) -> &mut TextEntry {
+        match self.request_tabs_widget.get_current_tab().as_str() {
+            "HEADER" => &mut self.header_widget,
+            "QUERY" => &mut self.query_widget,
+            "TESTS" => &mut self.tests_widget,
+            "CAPTURE" => &mut self.capture_widget,
+            _ => &mut self.request_widget,
+        }
+    }
+}
+
+fn centered_rect(area: Rect, percent_x: u16, percent_y: u16) -> Rect {
+    let width = area.width * percent_x / 100;
+    let height = area.height * percent_y / 100;
+    Rect::new(
+        area.x + (area.width - width) / 2,
+        area.y + (area.height - height) / 2,
+        width,
+        height,
+    )
 }
 
-// Entry mode
+// Sorts parsed lines into valid values and failed results
+fn parse_entry_lines<T>(
+    parsed: Vec<Result<T, String>>,
+    description: &str,
+    invalid: &mut Vec<AssertionResult>,
+) -> Vec<T> {
+    let mut valid = vec![];
+    for line in parsed {
+        match line {
+            Ok(value) => valid.push(value),
+            Err(message) => invalid.push(AssertionResult {
+                description: description.to_string(),
+                passed: false,
+                message,
+            }),
+        }
+    }
+    valid
+}
+
+// Splits lines like "Name: value" or "key=value" into pairs
+fn parse_pairs(text: &str, separator: char) -> Vec<(String, String)> {
+    text.lines()
+        .filter_map(|line| {
+            let mut parts = line.splitn(2, separator);
+            let name = parts.next()?.trim();
+            if name.is_empty() {
+                return None;
+            }
+            let value = parts.next().unwrap_or("").trim();
+            Some((name.to_string(), value.to_string()))
+        })
+        .collect()
+}
+
+// Entry mode (the endpoint and request editors)
 impl App {
     pub fn exit_input(&mut self) {
         self.input_mode = InputMode::Navigation;
-        self.widget_styles[self.ui[self.current_pane]] = Color::Yellow;
+        self.pane_states[self.current_pane] = PaneState::Focused;
+    }
+
+    // The editor that keys go to in the current input mode
+    fn active_entry(&mut self) -> &mut TextEntry {
+        if self.input_mode == InputMode::EndpointEntry {
+            &mut self.endpoint_widget
+        } else {
+            self.entry_widget_mut()
+        }
     }
 
     pub fn backspace(&mut self) {
-        self.request_widget.backspace();
+        self.active_entry().backspace();
+    }
+
+    pub fn delete(&mut self) {
+        self.active_entry().delete();
     }
 
     pub fn input_tab(&mut self) {
-        self.request_widget.input_tab();
+        self.active_entry().input_tab();
     }
 
-    pub fn entry_left(&mut self) {
-        self.request_widget.cursor_left();
+    pub fn input_char(&mut self, c: char) {
+        self.active_entry().input_char(c);
     }
 
-    pub fn entry_right(&mut self) {
-        self.request_widget.cursor_right();
+    pub fn entry_move(&mut self, motion: Motion, select: bool) {
+        self.active_entry().move_cursor(motion, select);
     }
 
-    pub fn entry_up(&mut self) {
-        self.request_widget.cursor_up();
+    pub fn select_all(&mut self) {
+        self.active_entry().select_all();
     }
 
-    pub fn entry_down(&mut self) {
-        self.request_widget.cursor_down();
+    pub fn undo(&mut self) {
+        self.active_entry().undo();
     }
 
-    pub fn input_char(&mut self, c: char) {
-        self.request_widget.input_char(c);
+    pub fn redo(&mut self) {
+        self.active_entry().redo();
+    }
+
+    // Line numbers are shown or hidden in all the request editors together
+    pub fn toggle_line_numbers(&mut self) {
+        for entry in [
+            &mut self.request_widget,
+            &mut self.header_widget,
+            &mut self.query_widget,
+            &mut self.tests_widget,
+            &mut self.capture_widget,
+        ] {
+            entry.toggle_line_numbers();
+        }
+    }
+
+    pub fn copy(&mut self) {
+        if let Some(text) = self.active_entry().selected_text() {
+            self.copy_to_clipboard("selection", text);
+        }
+    }
+
+    pub fn cut(&mut self) {
+        if let Some(text) = self.active_entry().cut() {
+            self.copy_to_clipboard("selection", text);
+        }
+    }
+
+    pub fn paste(&mut self) {
+        // System clipboard pastes come through the terminal as paste events
+        let text = self.clipboard.clone();
+        self.active_entry().input_str(&text);
+    }
+
+    // Text pasted into the terminal arrives in one piece, and goes to
+    // whichever editor is being typed in
+    pub fn paste_text(&mut self, text: &str) {
+        match self.input_mode {
+            InputMode::Entry | InputMode::EndpointEntry => self.active_entry().input_str(text),
+            // The palette is one line, so a multi-line curl command has its
+            // line continuations joined up
+            InputMode::Palette => {
+                let text = text.replace("\\\r\n", " ").replace("\\\n", " ");
+                if let Some(palette) = self.palette.as_mut() {
+                    palette.input_str(&text.replace(['\r', '\n'], " "));
+                }
+            }
+            _ => {}
+        }
+    }
+}
+
+// Help
+impl App {
+    pub fn help_visible(&self) -> bool {
+        self.help.is_some()
+    }
+
+    pub fn close_help(&mut self) {
+        self.help = None;
+    }
+
+    pub fn help_move(&mut self, lines: isize) {
+        if let Some(help) = self.help.as_mut() {
+            help.scroll_by(lines);
+        }
+    }
+
+    pub fn help_jump(&mut self, top: bool) {
+        if let Some(help) = self.help.as_mut() {
+            help.scroll_to(top);
+        }
+    }
+
+    // Every key binding, straight from the keymap, grouped by mode. The
+    // current mode comes first.
+    pub fn open_key_help(&mut self) {
+        let heading = |text: String| {
+            Spans::from(Span::styled(
+                text,
+                Style::default()
+                    .fg(self.theme.accent)
+                    .add_modifier(Modifier::BOLD),
+            ))
+        };
+        let modes = std::iter::once(self.input_mode).chain(
+            MODES
+                .iter()
+                .copied()
+                .filter(|mode| *mode != self.input_mode),
+        );
+
+        let mut lines = vec![];
+        let mut sections: Vec<(String, &str)> = modes
+            .map(|mode| {
+                (
+                    format!("{} · {}", mode.name(), mode.description()),
+                    mode.context(),
+                )
+            })
+            .collect();
+        sections.push(("EVERYWHERE".to_string(), "global"));
+        for (title, context) in sections {
+            lines.push(heading(title));
+            for (keys, action) in self.keymap.help(context, false) {
+                lines.push(Spans::from(vec![
+                    Span::styled(
+                        format!("  {:<22} ", keys),
+                        Style::default().fg(self.theme.accent),
+                    ),
+                    Span::styled(action.info().title, Style::default().fg(self.theme.text)),
+                    Span::styled(
+                        format!(" ({})", action),
+                        Style::default().fg(self.theme.dim),
+                    ),
+                ]));
+            }
+            lines.push(Spans::from(""));
+        }
+        self.help = Some(HelpPage::new(
+            "Keys (any other key closes)".to_string(),
+            lines,
+        ));
+    }
+
+    // How to get around, with the keys from the keymap
+    pub fn open_welcome(&mut self) {
+        let keys = |context: &str, actions: &[Action]| {
+            actions
+                .iter()
+                .filter_map(|action| self.keymap.keys_for(context, *action))
+                .filter_map(|keys| keys.split(", ").next().map(str::to_string))
+                .collect::<Vec<String>>()
+                .join("/")
+        };
+        let key_line = |keys: String, text: &str| {
+            Spans::from(vec![
+                Span::styled(
+                    format!("  {:<10}", keys),
+                    Style::default().fg(self.theme.accent),
+                ),
+                Span::styled(text.to_string(), Style::default().fg(self.theme.text)),
+            ])
+        };
+        let text = |text: &str| {
+            Spans::from(Span::styled(
+                text.to_string(),
+                Style::default().fg(self.theme.text),
+            ))
+        };
+        let heading = |text: &str| {
+            Spans::from(Span::styled(
+                text.to_string(),
+                Style::default()
+                    .fg(self.theme.accent)
+                    .add_modifier(Modifier::BOLD),
+            ))
+        };
+        let navigation = InputMode::Navigation.context();
+        let lines = vec![
+            heading("Welcome to HTTP Request Tool"),
+            text(""),
+            text("The screen is made of panes: the URL, the request tabs"),
+            text("and editor, the method and SEND, then the response."),
+            text("The focused pane has a coloured border."),
+            text(""),
+            heading("Getting around"),
+            key_line(
+                keys(
+                    navigation,
+                    &[Action::Left, Action::Down, Action::Up, Action::Right],
+                ),
+                "move to the next pane in that direction",
+            ),
+            key_line(
+                keys(navigation, &[Action::Enter]),
+                "use the pane: edit it, pick a tab, or send",
+            ),
+            key_line(
+                keys(InputMode::Entry.context(), &[Action::Exit]),
+                "stop editing and go back to moving",
+            ),
+            text(""),
+            heading("Everything else"),
+            key_line(keys(navigation, &[Action::Palette]), "search every command"),
+            key_line(
+                keys(navigation, &[Action::Help]),
+                "list the keys for every mode",
+            ),
+            key_line(keys(navigation, &[Action::Quit]), "quit"),
+            text(""),
+            text("The bar at the bottom shows the mode you're in and its"),
+            text("main keys. \"Show the welcome screen\" in the command"),
+            text("palette brings this back."),
+        ];
+        self.help = Some(HelpPage::new("Welcome (any key starts)".to_string(), lines));
+    }
+}
+
+// Command palette
+impl App {
+    pub fn open_command_palette(&mut self) {
+        let context = self.input_mode.context();
+        let items = ACTIONS
+            .iter()
+            .filter(|info| info.command)
+            .map(|info| {
+                let keys = self.keymap.keys_for(context, info.action);
+                (info.title.to_string(), keys.unwrap_or_default())
+            })
+            .collect();
+        self.open_palette(PaletteKind::Commands, "Commands", items);
+    }
+
+    fn open_palette(&mut self, kind: PaletteKind, title: &str, items: Vec<(String, String)>) {
+        if self.input_mode != InputMode::Palette {
+            self.palette_return = self.input_mode;
+        }
+        self.input_mode = InputMode::Palette;
+        self.palette = Some(Palette::new(kind, title, items));
+    }
+
+    pub fn close_palette(&mut self) {
+        self.palette = None;
+        self.input_mode = self.palette_return;
+    }
+
+    pub fn palette_move(&mut self, lines: isize) {
+        if let Some(palette) = self.palette.as_mut() {
+            palette.move_by(lines);
+        }
+    }
+
+    pub fn palette_input(&mut self, c: char) {
+        if let Some(palette) = self.palette.as_mut() {
+            palette.input_char(c);
+        }
+    }
+
+    pub fn palette_backspace(&mut self) {
+        if let Some(palette) = self.palette.as_mut() {
+            palette.backspace();
+        }
+    }
+
+    // Closes the palette and acts on what was chosen. A chosen command is
+    // returned for the caller to perform, since some need the terminal.
+    pub fn palette_choice(&mut self) -> Option<Action> {
+        let palette = self.palette.take()?;
+        self.close_palette();
+        let label = palette.selected_label().map(str::to_string);
+        let result = match (palette.kind, label) {
+            (PaletteKind::Commands, Some(label)) => {
+                return ACTIONS
+                    .iter()
+                    .find(|info| info.title == label)
+                    .map(|info| info.action)
+            }
+            (PaletteKind::Environments, Some(name)) => self.switch_environment(&name),
+            (PaletteKind::Themes, Some(name)) => self.switch_theme(&name),
+            (PaletteKind::CollectionItems, Some(path)) => self.open_collection_item(&path),
+            (PaletteKind::Curl, _) => curl::parse(&palette.query)
+                .map_err(|e| e.into())
+                .and_then(|request| self.load_request(&request, Variables::new())),
+            (_, None) => Ok(()),
+        };
+        if let Err(e) = result {
+            self.response_body = format!("Error: {}", e);
+        }
+        None
+    }
+
+    // Environments are the files in `environments/`
+    pub fn choose_environment(&mut self) {
+        let mut names: Vec<String> = fs::read_dir("environments")
+            .into_iter()
+            .flatten()
+            .flatten()
+            .map(|entry| entry.path())
+            .filter(|path| {
+                path.extension()
+                    .is_some_and(|extension| extension == "json")
+            })
+            .filter_map(|path| Some(path.file_stem()?.to_string_lossy().into_owned()))
+            .collect();
+        names.sort();
+        if names.is_empty() {
+            self.notice = "No environments in ./environments".to_string();
+            return;
+        }
+        let current = self.environment.name.clone();
+        let items = names
+            .into_iter()
+            .map(|name| {
+                let detail = if name == current { "current" } else { "" };
+                (name, detail.to_string())
+            })
+            .collect();
+        self.open_palette(PaletteKind::Environments, "Environments", items);
+    }
+
+    fn switch_environment(&mut self, name: &str) -> Result<(), Box<dyn Error>> {
+        let environment = Environment::find(name)?;
+        self.notice = format!("Environment: {}", environment.name);
+        self.environment = environment;
+        Ok(())
+    }
+
+    pub fn choose_theme(&mut self) {
+        let items = Theme::names(&self.config)
+            .into_iter()
+            .map(|name| {
+                let detail = if name == self.theme.name {
+                    "current"
+                } else {
+                    ""
+                };
+                (name, detail.to_string())
+            })
+            .collect();
+        self.open_palette(PaletteKind::Themes, "Themes", items);
+    }
+
+    fn switch_theme(&mut self, name: &str) -> Result<(), Box<dyn Error>> {
+        self.theme = Theme::named(name, &self.config)?;
+        Ok(())
+    }
+
+    pub fn choose_collection_item(&mut self) {
+        let items = match &self.collection {
+            Some(collection) => collection.items(None).unwrap_or_default(),
+            None => {
+                self.notice = format!("No {} in this directory", COLLECTION_FILE);
+                return;
+            }
+        };
+        let items = items
+            .into_iter()
+            .map(|item| (item.path, item.request.method))
+            .collect();
+        self.open_palette(PaletteKind::CollectionItems, "Collection", items);
+    }
+
+    fn open_collection_item(&mut self, path: &str) -> Result<(), Box<dyn Error>> {
+        let item = self
+            .collection
+            .as_ref()
+            .ok_or_else(|| format!("No {} in this directory", COLLECTION_FILE))?
+            .item(path)?;
+        self.load_request(&item.request, item.variables)
+    }
+
+    // Applies the command-line arguments. A request goes in the first tab,
+    // or a new one if the restored session has work in it.
+    pub fn launch(&mut self, args: LaunchArgs) -> Result<(), Box<dyn Error>> {
+        if let Some(path) = &args.collection {
+            let collection = Collection::load(path)?;
+            let workflow_names = collection
+                .workflows
+                .iter()
+                .map(|workflow| workflow.name.clone())
+                .collect();
+            self.workflow_view = WorkflowView::new(workflow_names);
+            self.collection = Some(collection);
+        }
+        if let Some(name) = &args.environment {
+            self.switch_environment(name)?;
+        }
+        if let Some(item) = args.request(self.collection.as_ref())? {
+            self.save_tab();
+            if !self.tabs[self.current_tab].is_blank() {
+                self.new_tab();
+            }
+            self.load_request(&item.request, item.variables)?;
+            // Reloaded, so undoing can't empty the tab again
+            self.save_tab();
+            self.load_tab(self.current_tab);
+            self.focus(PANE_SEND_BUTTON);
+        }
+        if args.send {
+            self.send();
+        }
+        Ok(())
+    }
+
+    pub fn import_curl(&mut self) {
+        self.open_palette(PaletteKind::Curl, "Import curl", vec![]);
+    }
+
+    // Fills the editors in with a request
+    fn load_request(
+        &mut self,
+        request: &RequestSpec,
+        variables: Variables,
+    ) -> Result<(), Box<dyn Error>> {
+        // Less common methods, like PROPFIND, get their own tab
+        if Method::from_bytes(request.method.as_bytes()).is_err() {
+            return Err(format!("invalid method {}", request.method).into());
+        }
+        self.method_select_widget.select_or_add(&request.method);
+        let lines = |lines: Vec<String>| lines.join("\n");
+        self.endpoint_widget.set_text(request.url.clone());
+        self.header_widget.set_text(lines(
+            request
+                .headers
+                .iter()
+                .map(|(name, value)| format!("{}: {}", name, value))
+                .collect(),
+        ));
+        self.query_widget.set_text(lines(
+            request
+                .query
+                .iter()
+                .map(|(name, value)| format!("{}={}", name, value))
+                .collect(),
+        ));
+        self.request_widget.set_text(request.body.clone());
+        self.tests_widget.set_text(lines(
+            request.assertions.iter().map(|a| a.to_string()).collect(),
+        ));
+        self.capture_widget.set_text(lines(
+            request.extract.iter().map(|e| e.to_string()).collect(),
+        ));
+        self.item_variables = variables;
+        Ok(())
+    }
+}
+
+// Session
+impl App {
+    // Everything needed to pick up where the user left off. Only the last
+    // response of each tab is kept.
+    fn session(&mut self) -> Session {
+        self.save_tab();
+        let mut tabs = self.tabs.clone();
+        for tab in tabs.iter_mut() {
+            let keep = tab.history.len().saturating_sub(1);
+            tab.history.drain(..keep);
+        }
+        Session {
+            tabs,
+            current_tab: self.current_tab,
+            focused_pane: self.current_pane,
+            environment: self.environment.clone(),
+            runtime_variables: self.runtime_variables.clone(),
+        }
+    }
+
+    fn save_session(&mut self) -> Result<(), Box<dyn Error>> {
+        let json = serde_json::to_string(&self.session())?;
+        if json != self.saved_session {
+            Session::write(&json)?;
+            self.saved_session = json;
+        }
+        Ok(())
+    }
+
+    fn restore(&mut self, session: Session) {
+        self.tabs = session.tabs;
+        self.load_tab(session.current_tab.min(self.tabs.len() - 1));
+        self.focus(session.focused_pane.min(PANE_COUNT - 1));
+        self.environment = session.environment;
+        self.runtime_variables = session.runtime_variables;
+    }
+}
+
+// Status bar
+impl App {
+    // The focused pane, environment, request state and last result, then
+    // any notice
+    fn status_spans(&self) -> Vec<Span<'_>> {
+        let mut parts = vec![Span::raw(layout::PANE_NAMES[self.current_pane])];
+        parts.push(match self.environment.name.as_str() {
+            "" => Span::styled("no environment", Style::default().fg(self.theme.dim)),
+            name => Span::raw(name),
+        });
+        let last_entry = self.history.last();
+        parts.push(match (self.request_state, last_entry) {
+            (RequestState::Sending, _) => {
+                let elapsed = self
+                    .pending
+                    .as_ref()
+                    .map_or(0.0, |pending| pending.started.elapsed().as_secs_f64());
+                Span::styled(
+                    format!("Sending… {:.1} s", elapsed),
+                    Style::default().fg(self.theme.accent),
+                )
+            }
+            (RequestState::Failed, _) => {
+                Span::styled("Failed", Style::default().fg(self.theme.error))
+            }
+            (RequestState::Idle, Some(entry)) => Span::styled(
+                format!(
+                    "{} in {} ms",
+                    entry.response.status,
+                    entry.response.timings.total().as_millis()
+                ),
+                Style::default().fg(self.theme.status(entry.response.status)),
+            ),
+            (RequestState::Idle, None) => Span::styled("Idle", Style::default().fg(self.theme.dim)),
+        });
+        if self.request_state == RequestState::Idle && !self.test_results.is_empty() {
+            let passed = self.test_results.iter().filter(|r| r.passed).count();
+            let colour = if passed == self.test_results.len() {
+                self.theme.success
+            } else {
+                self.theme.error
+            };
+            parts.push(Span::styled(
+                format!("{}/{} tests passed", passed, self.test_results.len()),
+                Style::default().fg(colour),
+            ));
+        }
+        if !self.notice.is_empty() {
+            parts.push(Span::styled(
+                self.notice.as_str(),
+                Style::default().fg(self.theme.accent),
+            ));
+        }
+
+        let mut spans = vec![];
+        for (i, part) in parts.into_iter().enumerate() {
+            if i > 0 {
+                spans.push(Span::styled(" · ", Style::default().fg(self.theme.dim)));
+            }
+            spans.push(part);
+        }
+        spans
+    }
+
+    // The main keys for the focused pane and mode, e.g. that a tab bar is
+    // left with `esc`
+    fn key_hints(&self) -> Vec<(String, &'static str)> {
+        use Action::*;
+        let hints: Vec<(&[Action], &str)> = match self.input_mode {
+            InputMode::Navigation => {
+                let enter = match self.current_pane {
+                    PANE_TABS | PANE_BODY_HEADER_SELECT | PANE_RESPONSE_TABS => "choose tab",
+                    PANE_ENDPOINT => "edit URL",
+                    PANE_REQUEST_ENTRY => "edit",
+                    PANE_METHOD_SELECT => "choose method",
+                    PANE_SEND_BUTTON => "send",
+                    _ => "read",
+                };
+                vec![
+                    (&[Left, Down, Up, Right], "move"),
+                    (&[Enter], enter),
+                    (&[Palette], "commands"),
+                    (&[Help], "help"),
+                    (&[Quit], "quit"),
+                ]
+            }
+            InputMode::TabSelect => vec![
+                (&[Left, Right], "switch"),
+                (&[DeleteTab], "close"),
+                (&[Exit], "done"),
+            ],
+            InputMode::Entry => vec![
+                (&[Exit], "done"),
+                (&[ExternalEditor], "external editor"),
+                (&[Undo], "undo"),
+                (&[Help], "help"),
+            ],
+            InputMode::EndpointEntry => vec![(&[Exit], "done"), (&[Help], "help")],
+            InputMode::BodyHeaderSelect | InputMode::MethodSelect | InputMode::ResponseSelect => {
+                vec![(&[Left, Right], "switch"), (&[Exit], "done")]
+            }
+            InputMode::ResponseView => vec![
+                (&[Down, Up], "move"),
+                (&[PageUp, PageDown], "page"),
+                (&[CopyNode], "copy value"),
+                (&[Exit], "done"),
+            ],
+            InputMode::Workflow => vec![
+                (&[Up, Down], "choose"),
+                (&[Enter], "run"),
+                (&[Exit], "close"),
+            ],
+            InputMode::Palette => vec![
+                (&[Up, Down], "choose"),
+                (&[Enter], "run"),
+                (&[Exit], "close"),
+            ],
+        };
+
+        let context = self.input_mode.context();
+        hints
+            .into_iter()
+            .filter_map(|(actions, label)| Some((self.first_keys(context, actions)?, label)))
+            .collect()
+    }
+
+    // The first key of each action joined up, e.g. `j/k`, skipping actions
+    // without one
+    fn first_keys(&self, context: &str, actions: &[Action]) -> Option<String> {
+        let keys: Vec<String> = actions
+            .iter()
+            .filter_map(|action| self.keymap.keys_for(context, *action))
+            .filter_map(|keys| keys.split(", ").next().map(str::to_string))
+            .collect();
+        if keys.is_empty() {
+            None
+        } else {
+            Some(keys.join("/"))
+        }
+    }
+
+    // Hints written into the UI, like `ctrl+f format`, using the keys as
+    // they're currently bound
+    fn hint_text(&self, context: &str, hints: &[(&[Action], &str)], separator: &str) -> String {
+        hints
+            .iter()
+            .filter_map(|(actions, label)| {
+                Some(format!("{} {}", self.first_keys(context, actions)?, label))
+            })
+            .collect::<Vec<_>>()
+            .join(separator)
+    }
+}
+
+// Layout
+impl App {
+    // Changes the layout and saves it for next time
+    pub fn change_layout(&mut self, change: impl FnOnce(&mut layout::Layout)) {
+        change(&mut self.layout);
+        if let Err(e) = self.layout.save() {
+            self.notice = format!("Could not save the layout: {}", e);
+        }
+    }
+
+    // Shows only the side of the focused pane, or both again. When the
+    // terminal only has room for one side, switches to the other one.
+    pub fn toggle_zoom(&mut self) {
+        let side = layout::side(self.current_pane);
+        if self.fit == Fit::SinglePane {
+            self.exit_input();
+            self.focus(match side {
+                Side::Request => PANE_RESPONSE_TEXT,
+                Side::Response => PANE_REQUEST_ENTRY,
+            });
+            return;
+        }
+        self.change_layout(|layout| {
+            layout.zoom = match layout.zoom {
+                Some(_) => None,
+                None => Some(side),
+            }
+        });
+    }
+
+    // Moves the focus off a pane that was hidden, stopping any editing in it
+    fn focus_visible_pane(&mut self) {
+        if self.pane_areas[self.current_pane].area() > 0 {
+            return;
+        }
+        if let Some(pane) = (0..PANE_COUNT).find(|pane| self.pane_areas[*pane].area() > 0) {
+            if !matches!(self.input_mode, InputMode::Palette | InputMode::Workflow) {
+                self.input_mode = InputMode::Navigation;
+            }
+            self.focus(pane);
+        }
+    }
+}
+
+// Mouse
+impl App {
+    // Focuses the pane that was clicked, and acts on the click as if it
+    // were entered with the keyboard
+    pub fn click(&mut self, column: u16, row: u16) {
+        if self.workflow_view.visible || self.palette.is_some() {
+            return;
+        }
+        let pane = match self.pane_at(column, row) {
+            Some(pane) => pane,
+            None => return,
+        };
+        let area = self.pane_areas[pane];
+        let (x, y) = (column - area.x, row - area.y);
+
+        self.input_mode = InputMode::Navigation;
+        self.focus(pane);
+        match pane {
+            PANE_TABS => {
+                let widths: Vec<usize> = (1..self.tabs.len() + 1)
+                    .map(|num| num.to_string().len())
+                    .collect();
+                if let Some(tab) = tab_select::tab_at(&widths, x) {
+                    self.switch_tab(tab);
+                }
+            }
+            PANE_BODY_HEADER_SELECT => {
+                self.request_tabs_widget.click(x);
+            }
+            PANE_METHOD_SELECT => {
+                self.method_select_widget.click(x);
+            }
+            PANE_RESPONSE_TABS => {
+                if self.response_tabs_widget.click(x) {
+                    self.response_view.reset();
+                }
+            }
+            PANE_ENDPOINT | PANE_REQUEST_ENTRY => {
+                self.enter();
+                self.active_entry()
+                    .click(x.saturating_sub(1), y.saturating_sub(1));
+            }
+            PANE_RESPONSE_TEXT => {
+                self.enter();
+                self.response_view.click(y);
+            }
+            _ => self.enter(),
+        }
+    }
+
+    // The mouse wheel scrolls whichever pane it's over
+    pub fn scroll_at(&mut self, column: u16, row: u16, lines: isize) {
+        if self.workflow_view.visible || self.palette.is_some() {
+            return;
+        }
+        match self.pane_at(column, row) {
+            Some(PANE_REQUEST_ENTRY) => self.entry_widget_mut().scroll_lines(lines),
+            Some(PANE_RESPONSE_TEXT) => {
+                let line_count = self.response_line_count();
+                self.response_view.scroll_by(lines, line_count);
+            }
+            _ => {}
+        }
+    }
+
+    fn pane_at(&self, column: u16, row: u16) -> Option<usize> {
+        self.pane_areas.iter().position(|area| {
+            column >= area.x
+                && column < area.x + area.width
+                && row >= area.y
+                && row < area.y + area.height
+        })
+    }
+
+    fn focus(&mut self, pane: usize) {
+        self.pane_states[self.current_pane] = PaneState::Idle;
+        self.current_pane = pane;
+        self.pane_states[pane] = PaneState::Focused;
+    }
+}
+
+// Clipboard
+impl App {
+    pub fn copy_response_body(&mut self) {
+        self.copy_to_clipboard("response body", self.response_body.clone());
+    }
+
+    pub fn copy_response_headers(&mut self) {
+        let headers = match self.history.last() {
+            Some(entry) => entry
+                .response
+                .headers
+                .iter()
+                .map(|(name, value)| format!("{}: {}", name, value))
+                .collect::<Vec<String>>()
+                .join("\n"),
+            None => return,
+        };
+        self.copy_to_clipboard("headers", headers);
+    }
+
+    pub fn copy_url(&mut self) {
+        // Fall back to the URL as typed if its variables can't be filled in
+        let url = self
+            .build_request(vec![], vec![])
+            .and_then(|(request, _)| request.full_url())
+            .map(|url| url.to_string())
+            .unwrap_or_else(|_| self.endpoint_widget.get_text());
+        self.copy_to_clipboard("URL", url);
+    }
+
+    pub fn copy_as_curl(&mut self) {
+        match self
+            .build_request(vec![], vec![])
+            .and_then(|(request, _)| request.to_curl())
+        {
+            Ok(command) => self.copy_to_clipboard("curl command", command),
+            Err(e) => self.notice = format!("Can't copy as curl: {}", e),
+        }
+    }
+
+    // The JSON value starting on the response view's cursor line, or just
+    // the line for other responses
+    pub fn copy_selected_node(&mut self) {
+        if self.response_tabs_widget.get_current_tab() != "BODY" {
+            return;
+        }
+        let body = self.displayed_body();
+        let line = self.response_view.cursor;
+        let text = if self.response_is_json() {
+            match json_text::node_at_line(&body, line) {
+                Some(range) => json_text::format(&body[range]),
+                None => return,
+            }
+        } else {
+            body.split('\n').nth(line).unwrap_or_default().to_string()
+        };
+        self.copy_to_clipboard("selection", text);
+    }
+
+    fn copy_to_clipboard(&mut self, what: &str, text: String) {
+        self.notice = match clipboard::copy(&text) {
+            Ok(method) => format!("Copied {} ({})", what, method),
+            Err(e) => format!("Copy failed: {}", e),
+        };
+        // Keep it for pasting into the editors too
+        self.clipboard = text;
+    }
+}
+
+// Response view
+impl App {
+    pub fn response_move(&mut self, lines: isize) {
+        let line_count = self.response_line_count();
+        self.response_view.move_by(lines, line_count);
+    }
+
+    pub fn response_top(&mut self) {
+        self.response_view.reset();
+    }
+
+    pub fn response_bottom(&mut self) {
+        let line_count = self.response_line_count();
+        self.response_view.move_to_end(line_count);
+    }
+
+    pub fn toggle_wrap(&mut self) {
+        self.response_view.toggle_wrap();
+    }
+
+    fn response_line_count(&self) -> usize {
+        self.response_line_widths().len()
+    }
+
+    // How wide each line of the response tab is, for wrapping
+    fn response_line_widths(&self) -> Vec<usize> {
+        let lines: Vec<String> = match self.response_tabs_widget.get_current_tab().as_str() {
+            "HEADER" => self
+                .history
+                .last()
+                .map(|entry| {
+                    entry
+                        .response
+                        .headers
+                        .iter()
+                        .map(|(name, value)| format!("{}: {}", name, value))
+                        .collect()
+                })
+                .unwrap_or_default(),
+            "TESTS" => self
+                .test_results
+                .iter()
+                .map(|result| format!("✓ {} ({})", result.description, result.message))
+                .collect(),
+            _ => self
+                .displayed_body()
+                .split('\n')
+                .map(String::from)
+                .collect(),
+        };
+        lines.iter().map(|line| line.width()).collect()
     }
 }
 
@@ -432,3 +2009,110 @@ impl App {
         self.method_select_widget.move_right();
     }
 }
+
+// Body/header/query/tests/capture select
+impl App {
+    pub fn request_tab_left(&mut self) {
+        self.request_tabs_widget.move_left();
+    }
+
+    pub fn request_tab_right(&mut self) {
+        self.request_tabs_widget.move_right();
+    }
+}
+
+// Response tabs
+impl App {
+    pub fn response_tab_left(&mut self) {
+        self.response_tabs_widget.move_left();
+        self.response_view.reset();
+    }
+
+    pub fn response_tab_right(&mut self) {
+        self.response_tabs_widget.move_right();
+        self.response_view.reset();
+    }
+}
+
+// Workflows
+impl App {
+    pub fn open_workflows(&mut self) {
+        self.exit_input();
+        self.workflow_view.visible = true;
+        self.input_mode = InputMode::Workflow;
+    }
+
+    pub fn close_workflows(&mut self) {
+        self.workflow_view.visible = false;
+        self.input_mode = InputMode::Navigation;
+    }
+
+    pub fn workflow_up(&mut self) {
+        self.workflow_view.move_up();
+    }
+
+    pub fn workflow_down(&mut self) {
+        self.workflow_view.move_down();
+    }
+
+    // Runs the selected workflow on another thread, so that the UI can show
+    // each step's progress as it happens
+    pub fn workflow_start(&mut self) {
+        let running = self.workflow_view.run.as_ref();
+        if running.is_some_and(|run| !run.is_finished()) {
+            return;
+        }
+        let (collection, name) = match (
+            &self.collection,
+            self.workflow_view.names.get(self.workflow_view.selected),
+        ) {
+            (Some(collection), Some(name)) => (collection, name),
+            _ => return,
+        };
+
+        let graph = match WorkflowGraph::build(collection, name) {
+            Ok(graph) => Arc::new(graph),
+            Err(e) => {
+                self.workflow_view.error = Some(e);
+                return;
+            }
+        };
+        self.workflow_view.error = None;
+
+        let (tx, rx) = mpsc::channel();
+        let environment = self.environment.variables.clone();
+        let runtime = self.runtime_variables.clone();
+        let thread_graph = Arc::clone(&graph);
+        thread::spawn(move || {
+            let run = workflow::execute(
+                &thread_graph,
+                environment,
+                runtime,
+                Undefined::Warn,
+                |node, status, result| {
+                    let summary = result.map(workflow_view::summarize).unwrap_or_default();
+                    let _ = tx.send(RunEvent::Step(node, status, summary));
+                },
+            );
+            let _ = tx.send(RunEvent::Finished(run));
+        });
+        self.workflow_view.run = Some(ActiveRun::new(graph, rx));
+    }
+
+    // Called regularly by the main loop to pick up background work
+    pub fn tick(&mut self) {
+        self.receive_response();
+        if let Some(run) = self.workflow_view.run.as_mut() {
+            if let Some(finished) = run.update() {
+                self.environment.variables.extend(finished.environment);
+                self.runtime_variables.extend(finished.runtime);
+            }
+        }
+        if self.last_autosave.elapsed() >= AUTOSAVE_INTERVAL {
+            self.last_autosave = Instant::now();
+            if let Err(e) = self.save_session() {
+                self.notice = format!("Could not save the session: {}", e);
+            }
+        }
+    }
+}
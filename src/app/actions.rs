@@ -0,0 +1,173 @@
+use super::{App, InputMode, Motion};
+use crate::action::Action;
+use crate::keymap::Key;
+use crossterm::event::{KeyCode, KeyModifiers};
+
+// Runs an action from the keymap or the command palette. Commands work the
+// same everywhere; other actions depend on the input mode, e.g. `left` moves
+// the focus, the selected tab or the cursor. Quitting and the external editor
+// need the terminal, so they're handled in main.rs.
+impl App {
+    pub fn perform(&mut self, action: Action) {
+        if self.perform_command(action) {
+            return;
+        }
+
+        match self.input_mode {
+            InputMode::Navigation => match action {
+                Action::Enter => self.enter(),
+                Action::Left => self.left(),
+                Action::Right => self.right(),
+                Action::Up => self.up(),
+                Action::Down => self.down(),
+                _ => {}
+            },
+            InputMode::TabSelect => match action {
+                Action::Exit => self.exit_input(),
+                Action::Left => self.tab_left(),
+                Action::Right => self.tab_right(),
+                _ => {}
+            },
+            InputMode::Entry | InputMode::EndpointEntry => self.perform_edit(action),
+            InputMode::BodyHeaderSelect => match action {
+                Action::Exit => self.exit_input(),
+                Action::Left => self.request_tab_left(),
+                Action::Right => self.request_tab_right(),
+                _ => {}
+            },
+            InputMode::MethodSelect => match action {
+                Action::Exit => self.exit_input(),
+                Action::Left => self.method_select_left(),
+                Action::Right => self.method_select_right(),
+                _ => {}
+            },
+            InputMode::ResponseSelect => match action {
+                Action::Exit => self.exit_input(),
+                Action::Left => self.response_tab_left(),
+                Action::Right => self.response_tab_right(),
+                _ => {}
+            },
+            InputMode::ResponseView => match action {
+                Action::Exit => self.exit_input(),
+                Action::Up => self.response_move(-1),
+                Action::Down => self.response_move(1),
+                Action::PageUp => self.response_move(-20),
+                Action::PageDown => self.response_move(20),
+                Action::Top => self.response_top(),
+                Action::Bottom => self.response_bottom(),
+                Action::CopyNode => self.copy_selected_node(),
+                _ => {}
+            },
+            InputMode::Workflow => match action {
+                Action::Exit => self.close_workflows(),
+                Action::Enter => self.workflow_start(),
+                Action::Up => self.workflow_up(),
+                Action::Down => self.workflow_down(),
+                _ => {}
+            },
+            InputMode::Palette => match action {
+                Action::Exit => self.close_palette(),
+                Action::Up => self.palette_move(-1),
+                Action::Down => self.palette_move(1),
+                Action::Backspace => self.palette_backspace(),
+                _ => {}
+            },
+        }
+    }
+
+    // The help page scrolls with the `help` keys. Anything else closes it.
+    pub fn perform_help(&mut self, action: Action) {
+        match action {
+            Action::Up => self.help_move(-1),
+            Action::Down => self.help_move(1),
+            Action::PageUp => self.help_move(-10),
+            Action::PageDown => self.help_move(10),
+            Action::Top => self.help_jump(true),
+            Action::Bottom => self.help_jump(false),
+            _ => self.close_help(),
+        }
+    }
+
+    // Returns whether the action was a command
+    fn perform_command(&mut self, action: Action) -> bool {
+        match action {
+            Action::Help => self.open_key_help(),
+            Action::Welcome => self.open_welcome(),
+            Action::Palette if self.input_mode == InputMode::Palette => self.close_palette(),
+            Action::Palette => self.open_command_palette(),
+            Action::Send => self.send(),
+            Action::NewTab => self.new_tab(),
+            Action::DeleteTab => self.tab_delete(),
+            Action::SwitchEnvironment => self.choose_environment(),
+            Action::OpenCollectionItem => self.choose_collection_item(),
+            Action::ImportCurl => self.import_curl(),
+            Action::OpenWorkflows => self.open_workflows(),
+            Action::ToggleWrap => self.toggle_wrap(),
+            Action::ChangeTheme => self.choose_theme(),
+            Action::GrowRequest => self.change_layout(|layout| layout.resize(1)),
+            Action::ShrinkRequest => self.change_layout(|layout| layout.resize(-1)),
+            Action::ToggleStacked => self.change_layout(|layout| layout.stacked = !layout.stacked),
+            Action::ToggleZoom => self.toggle_zoom(),
+            Action::ToggleTabs => self.change_layout(|layout| layout.hide_tabs = !layout.hide_tabs),
+            Action::ToggleTiming => {
+                self.change_layout(|layout| layout.hide_timing = !layout.hide_timing)
+            }
+            Action::ToggleLineNumbers => self.toggle_line_numbers(),
+            Action::FormatJson => self.format_body(false),
+            Action::MinifyJson => self.format_body(true),
+            Action::CopyBody => self.copy_response_body(),
+            Action::CopyHeaders => self.copy_response_headers(),
+            Action::CopyUrl => self.copy_url(),
+            Action::CopyCurl => self.copy_as_curl(),
+            _ => return false,
+        }
+        true
+    }
+
+    fn perform_edit(&mut self, action: Action) {
+        match action {
+            Action::Exit => self.exit_input(),
+            Action::Left => self.entry_move(Motion::Left, false),
+            Action::Right => self.entry_move(Motion::Right, false),
+            Action::Up => self.entry_move(Motion::Up, false),
+            Action::Down => self.entry_move(Motion::Down, false),
+            Action::WordLeft => self.entry_move(Motion::WordLeft, false),
+            Action::WordRight => self.entry_move(Motion::WordRight, false),
+            Action::LineStart => self.entry_move(Motion::Home, false),
+            Action::LineEnd => self.entry_move(Motion::End, false),
+            Action::SelectLeft => self.entry_move(Motion::Left, true),
+            Action::SelectRight => self.entry_move(Motion::Right, true),
+            Action::SelectUp => self.entry_move(Motion::Up, true),
+            Action::SelectDown => self.entry_move(Motion::Down, true),
+            Action::SelectWordLeft => self.entry_move(Motion::WordLeft, true),
+            Action::SelectWordRight => self.entry_move(Motion::WordRight, true),
+            Action::SelectLineStart => self.entry_move(Motion::Home, true),
+            Action::SelectLineEnd => self.entry_move(Motion::End, true),
+            Action::SelectAll => self.select_all(),
+            Action::Backspace => self.backspace(),
+            Action::Delete => self.delete(),
+            Action::Newline => self.input_char('\n'),
+            Action::Indent => self.input_tab(),
+            Action::Undo => self.undo(),
+            Action::Redo => self.redo(),
+            Action::Copy => self.copy(),
+            Action::Cut => self.cut(),
+            Action::Paste => self.paste(),
+            _ => {}
+        }
+    }
+
+    // Keys without a binding are typed into the editors and the palette
+    pub fn type_key(&mut self, key: Key) {
+        let modified = key
+            .modifiers
+            .intersects(KeyModifiers::CONTROL | KeyModifiers::ALT);
+        if let (KeyCode::Char(c), false) = (key.code, modified) {
+            match self.input_mode {
+                InputMode::Entry | InputMode::EndpointEntry => self.input_char(c),
+                InputMode::Palette => self.palette_input(c),
+                _ => {}
+            }
+        }
+    }
+}
@@ -0,0 +1,61 @@
+use tui::{
+    layout::Rect,
+    style::{Color, Style},
+    text::Spans,
+    widgets::{Block, Borders, Paragraph},
+};
+
+// A page of text shown over the UI, like the key bindings or the welcome
+// screen. It scrolls with the `help` keys, and any other key closes it.
+pub struct HelpPage {
+    title: String,
+    lines: Vec<Spans<'static>>,
+    scroll: usize,
+    height: usize,
+}
+
+impl HelpPage {
+    pub fn new(title: String, lines: Vec<Spans<'static>>) -> HelpPage {
+        HelpPage {
+            title,
+            lines,
+            scroll: 0,
+            height: 1,
+        }
+    }
+
+    pub fn scroll_by(&mut self, lines: isize) {
+        let last = self.lines.len().saturating_sub(self.height);
+        self.scroll = (self.scroll as isize + lines).clamp(0, last as isize) as usize;
+    }
+
+    pub fn scroll_to(&mut self, top: bool) {
+        self.scroll = if top {
+            0
+        } else {
+            self.lines.len().saturating_sub(self.height)
+        };
+    }
+
+    // Keeps the scroll position in range when the page is resized
+    pub fn fit(&mut self, area: Rect) {
+        self.height = (area.height as usize).saturating_sub(2).max(1);
+        self.scroll_by(0);
+    }
+
+    pub fn get_widget(&self, colour: Color) -> Paragraph<'_> {
+        let more = if self.scroll + self.height < self.lines.len() {
+            " · more below"
+        } else {
+            ""
+        };
+        Paragraph::new(self.lines.clone())
+            .block(
+                Block::default()
+                    .title(format!("{}{}", self.title, more))
+                    .borders(Borders::ALL)
+                    .style(Style::default().fg(colour)),
+            )
+            .scroll((self.scroll as u16, 0))
+    }
+}
@@ -0,0 +1,253 @@
+use crate::theme::Theme;
+use serde::de::IgnoredAny;
+use std::ops::Range;
+use tui::{
+    style::Style,
+    text::{Span, Spans},
+};
+
+#[derive(Clone, Copy, PartialEq)]
+pub enum TokenKind {
+    Key,
+    String,
+    Number,
+    Literal,
+    Punctuation,
+    Variable,
+    Whitespace,
+    Other,
+}
+
+pub struct Token {
+    pub kind: TokenKind,
+    pub range: Range<usize>,
+}
+
+// Splits JSON text into tokens covering all of it. This is lexical only, so
+// it copes with the invalid JSON you have halfway through typing, and treats
+// `{{name}}` variables as single tokens.
+pub fn tokens(text: &str) -> Vec<Token> {
+    let bytes = text.as_bytes();
+    let mut tokens = vec![];
+    let mut i = 0;
+
+    while i < bytes.len() {
+        let start = i;
+        let kind = match bytes[i] {
+            b'"' => {
+                // Strings end at the closing quote, or the end of the line if
+                // it's missing
+                i += 1;
+                while i < bytes.len() && bytes[i] != b'"' && bytes[i] != b'\n' {
+                    i += if bytes[i] == b'\\' { 2 } else { 1 };
+                }
+                i = (i + 1).min(bytes.len());
+                let rest = text[i..].trim_start_matches([' ', '\t']);
+                if rest.starts_with(':') {
+                    TokenKind::Key
+                } else {
+                    TokenKind::String
+                }
+            }
+            b'{' if text[i..].starts_with("{{") && variable_end(text, i).is_some() => {
+                i = variable_end(text, i).unwrap();
+                TokenKind::Variable
+            }
+            b'{' | b'}' | b'[' | b']' | b',' | b':' => {
+                i += 1;
+                TokenKind::Punctuation
+            }
+            b'-' | b'0'..=b'9' => {
+                i += 1;
+                while i < bytes.len()
+                    && matches!(bytes[i], b'0'..=b'9' | b'.' | b'e' | b'E' | b'+' | b'-')
+                {
+                    i += 1;
+                }
+                TokenKind::Number
+            }
+            b' ' | b'\t' | b'\r' | b'\n' => {
+                while i < bytes.len() && matches!(bytes[i], b' ' | b'\t' | b'\r' | b'\n') {
+                    i += 1;
+                }
+                TokenKind::Whitespace
+            }
+            b'a'..=b'z' | b'A'..=b'Z' => {
+                while i < bytes.len() && bytes[i].is_ascii_alphanumeric() {
+                    i += 1;
+                }
+                match &text[start..i] {
+                    "true" | "false" | "null" => TokenKind::Literal,
+                    _ => TokenKind::Other,
+                }
+            }
+            _ => {
+                i += text[i..].chars().next().map_or(1, char::len_utf8);
+                TokenKind::Other
+            }
+        };
+        // A trailing backslash can step past the end of the text
+        i = i.min(bytes.len());
+        tokens.push(Token {
+            kind,
+            range: start..i,
+        });
+    }
+    tokens
+}
+
+fn variable_end(text: &str, start: usize) -> Option<usize> {
+    let end = text[start..].find("}}")? + start + 2;
+    if text[start..end].contains('\n') {
+        None
+    } else {
+        Some(end)
+    }
+}
+
+pub fn style(kind: TokenKind, theme: &Theme) -> Style {
+    match kind {
+        TokenKind::Key => Style::default().fg(theme.key),
+        TokenKind::String => Style::default().fg(theme.string),
+        TokenKind::Number => Style::default().fg(theme.number),
+        TokenKind::Literal => Style::default().fg(theme.literal),
+        TokenKind::Variable => Style::default().fg(theme.variable),
+        _ => Style::default(),
+    }
+}
+
+// Styled lines for read-only views like the response body
+pub fn highlight<'a>(text: &'a str, theme: &Theme) -> Vec<Spans<'a>> {
+    let mut lines = vec![];
+    let mut line = vec![];
+    for token in tokens(text) {
+        let style = style(token.kind, theme);
+        let mut parts = text[token.range].split('\n');
+        if let Some(first) = parts.next() {
+            line.push(Span::styled(first, style));
+        }
+        for part in parts {
+            lines.push(Spans::from(std::mem::take(&mut line)));
+            line.push(Span::styled(part, style));
+        }
+    }
+    lines.push(Spans::from(line));
+    lines
+}
+
+// Checks the text is JSON once its variables are filled in. Variables are
+// replaced with `0`, which is valid both in and out of strings and keeps
+// error lines in the right place.
+pub fn validate(text: &str) -> Result<(), serde_json::Error> {
+    if text.trim().is_empty() {
+        return Ok(());
+    }
+    let masked: String = tokens(text)
+        .into_iter()
+        .map(|token| match token.kind {
+            TokenKind::Variable => "0",
+            _ => &text[token.range],
+        })
+        .collect();
+    serde_json::from_str::<IgnoredAny>(&masked).map(|_| ())
+}
+
+// Re-indents the text without parsing it, so key order, number formatting
+// and variables are kept as written
+pub fn format(text: &str) -> String {
+    let tokens: Vec<&str> = tokens(text)
+        .into_iter()
+        .filter(|token| token.kind != TokenKind::Whitespace)
+        .map(|token| &text[token.range])
+        .collect();
+    let mut formatted = String::with_capacity(text.len());
+    let mut indent = 0;
+    let newline = |formatted: &mut String, indent: usize| {
+        formatted.push('\n');
+        formatted.push_str(&"  ".repeat(indent));
+    };
+
+    let mut i = 0;
+    while i < tokens.len() {
+        match tokens[i] {
+            open @ ("{" | "[") => {
+                formatted.push_str(open);
+                let close = if open == "{" { "}" } else { "]" };
+                if tokens.get(i + 1) == Some(&close) {
+                    formatted.push_str(close);
+                    i += 1;
+                } else {
+                    indent += 1;
+                    newline(&mut formatted, indent);
+                }
+            }
+            close @ ("}" | "]") => {
+                indent = indent.saturating_sub(1);
+                newline(&mut formatted, indent);
+                formatted.push_str(close);
+            }
+            "," => {
+                formatted.push(',');
+                newline(&mut formatted, indent);
+            }
+            ":" => formatted.push_str(": "),
+            token => formatted.push_str(token),
+        }
+        i += 1;
+    }
+    formatted
+}
+
+pub fn minify(text: &str) -> String {
+    tokens(text)
+        .into_iter()
+        .filter(|token| token.kind != TokenKind::Whitespace)
+        .map(|token| &text[token.range])
+        .collect()
+}
+
+// The extent of the value starting on the given line: the whole object or
+// array for an opening bracket, or the value after a key
+pub fn node_at_line(text: &str, line: usize) -> Option<Range<usize>> {
+    let line_start = match line {
+        0 => 0,
+        _ => text.match_indices('\n').nth(line - 1)?.0 + 1,
+    };
+    let tokens: Vec<Token> = tokens(text)
+        .into_iter()
+        .filter(|token| token.kind != TokenKind::Whitespace)
+        .collect();
+    let mut i = tokens
+        .iter()
+        .position(|token| token.range.start >= line_start)?;
+    if text[line_start..tokens[i].range.start].contains('\n') {
+        return None;
+    }
+
+    if tokens[i].kind == TokenKind::Key {
+        // Skip the key and its colon
+        i += 2;
+    }
+    let first = tokens.get(i)?;
+    match &text[first.range.clone()] {
+        "{" | "[" => {
+            let mut depth = 0;
+            for token in &tokens[i..] {
+                if token.kind != TokenKind::Punctuation {
+                    continue;
+                }
+                match &text[token.range.clone()] {
+                    "{" | "[" => depth += 1,
+                    "}" | "]" => depth -= 1,
+                    _ => {}
+                }
+                if depth == 0 {
+                    return Some(first.range.start..token.range.end);
+                }
+            }
+            None
+        }
+        "}" | "]" | "," | ":" => None,
+        _ => Some(first.range.clone()),
+    }
+}
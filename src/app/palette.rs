@@ -0,0 +1,165 @@
+use crate::theme::Theme;
+use std::cmp::Reverse;
+use tui::{
+    layout::Rect,
+    style::{Color, Modifier, Style},
+    text::{Span, Spans},
+    widgets::{Block, Borders, Paragraph},
+};
+use unicode_width::UnicodeWidthStr;
+
+// What the palette is choosing. Curl commands are typed or pasted rather
+// than picked from a list.
+#[derive(Clone, Copy, PartialEq)]
+pub enum PaletteKind {
+    Commands,
+    Environments,
+    Themes,
+    CollectionItems,
+    Curl,
+}
+
+// A fuzzy-searchable list, shown over the rest of the UI
+pub struct Palette {
+    pub kind: PaletteKind,
+    title: String,
+    // Each item's label, and a detail like its keys shown on the right
+    items: Vec<(String, String)>,
+    pub query: String,
+    selected: usize,
+}
+
+impl Palette {
+    pub fn new(kind: PaletteKind, title: &str, items: Vec<(String, String)>) -> Palette {
+        Palette {
+            kind,
+            title: title.to_string(),
+            items,
+            query: String::new(),
+            selected: 0,
+        }
+    }
+
+    pub fn input_char(&mut self, c: char) {
+        self.query.push(c);
+        self.selected = 0;
+    }
+
+    pub fn input_str(&mut self, text: &str) {
+        self.query.push_str(text);
+        self.selected = 0;
+    }
+
+    pub fn backspace(&mut self) {
+        self.query.pop();
+        self.selected = 0;
+    }
+
+    pub fn move_by(&mut self, lines: isize) {
+        let count = self.matches().len();
+        let selected = self.selected as isize + lines;
+        self.selected = selected.clamp(0, count.saturating_sub(1) as isize) as usize;
+    }
+
+    // Indices of the items matching the query, best first
+    fn matches(&self) -> Vec<usize> {
+        let mut scored: Vec<(usize, usize)> = self
+            .items
+            .iter()
+            .enumerate()
+            .filter_map(|(i, (label, _))| fuzzy_score(&self.query, label).map(|score| (score, i)))
+            .collect();
+        // Stable, so equally good matches keep their order
+        scored.sort_by_key(|(score, _)| Reverse(*score));
+        scored.into_iter().map(|(_, i)| i).collect()
+    }
+
+    // The label of the selected item
+    pub fn selected_label(&self) -> Option<&str> {
+        let i = *self.matches().get(self.selected)?;
+        Some(&self.items[i].0)
+    }
+
+    // The end of the query, as much as fits in `width` after the prompt
+    fn visible_query(&self, width: usize) -> &str {
+        let mut query = self.query.as_str();
+        while query.width() + 3 > width && !query.is_empty() {
+            let mut chars = query.chars();
+            chars.next();
+            query = chars.as_str();
+        }
+        query
+    }
+
+    pub fn cursor_xy(&self, area: Rect) -> (u16, u16) {
+        let width = area.width.saturating_sub(2) as usize;
+        (2 + self.visible_query(width).width() as u16, 0)
+    }
+
+    pub fn get_widget(&self, colour: Color, area: Rect, theme: &Theme) -> Paragraph<'_> {
+        let width = area.width.saturating_sub(2) as usize;
+        let height = area.height.saturating_sub(3) as usize;
+        let mut lines = vec![Spans::from(vec![
+            Span::styled("> ", Style::default().fg(theme.accent)),
+            Span::raw(self.visible_query(width)),
+        ])];
+
+        if self.kind == PaletteKind::Curl {
+            lines.push(Spans::from(Span::styled(
+                "Paste or type a curl command, then press enter",
+                Style::default().fg(theme.dim),
+            )));
+        } else {
+            let matches = self.matches();
+            if matches.is_empty() {
+                lines.push(Spans::from(Span::styled(
+                    "No matches",
+                    Style::default().fg(theme.dim),
+                )));
+            }
+            let first = (self.selected + 1).saturating_sub(height.max(1));
+            for (row, i) in matches.iter().enumerate().skip(first).take(height) {
+                let (label, detail) = &self.items[*i];
+                let gap = width.saturating_sub(label.width() + detail.width()).max(1);
+                let mut style = Style::default().fg(theme.text);
+                if row == self.selected {
+                    style = style.add_modifier(Modifier::REVERSED);
+                }
+                lines.push(Spans::from(vec![
+                    Span::styled(format!("{}{}", label, " ".repeat(gap)), style),
+                    Span::styled(detail.clone(), style.fg(theme.dim)),
+                ]));
+            }
+        }
+
+        Paragraph::new(lines).block(
+            Block::default()
+                .title(self.title.clone())
+                .borders(Borders::ALL)
+                .style(Style::default().fg(colour)),
+        )
+    }
+}
+
+// How well `text` matches `query`, if it does at all. Every character of the
+// query has to appear in order; runs of characters and matches at the start
+// of words score higher.
+pub fn fuzzy_score(query: &str, text: &str) -> Option<usize> {
+    let text: Vec<char> = text.to_lowercase().chars().collect();
+    let mut score = 0;
+    let mut position = 0;
+    let mut previous: Option<usize> = None;
+
+    for c in query.to_lowercase().chars().filter(|c| !c.is_whitespace()) {
+        let found = position + text[position..].iter().position(|t| *t == c)?;
+        if previous == Some(found.wrapping_sub(1)) {
+            score += 100;
+        }
+        if found == 0 || !text[found - 1].is_alphanumeric() {
+            score += 50;
+        }
+        previous = Some(found);
+        position = found + 1;
+    }
+    Some(score)
+}
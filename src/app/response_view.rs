@@ -0,0 +1,165 @@
+use tui::{
+    layout::Rect,
+    style::{Color, Style},
+    text::{Span, Spans},
+    widgets::{Block, Borders, Paragraph},
+};
+use unicode_width::UnicodeWidthChar;
+
+// A scrollable view of the response with a line cursor, used to pick out
+// part of the body
+pub struct ResponseView {
+    pub cursor: usize,
+    scroll: usize,
+    wrap: bool,
+    // The text width and the rows each line takes, as last drawn
+    width: usize,
+    heights: Vec<usize>,
+}
+
+impl ResponseView {
+    pub fn new() -> ResponseView {
+        ResponseView {
+            cursor: 0,
+            scroll: 0,
+            wrap: false,
+            width: 0,
+            heights: vec![],
+        }
+    }
+
+    pub fn reset(&mut self) {
+        self.cursor = 0;
+        self.scroll = 0;
+    }
+
+    pub fn toggle_wrap(&mut self) {
+        self.wrap = !self.wrap;
+    }
+
+    pub fn move_by(&mut self, lines: isize, line_count: usize) {
+        let cursor = self.cursor as isize + lines;
+        self.cursor = cursor.clamp(0, line_count.saturating_sub(1) as isize) as usize;
+    }
+
+    pub fn move_to_end(&mut self, line_count: usize) {
+        self.cursor = line_count.saturating_sub(1);
+    }
+
+    // Scrolls the view, taking the cursor along with it
+    pub fn scroll_by(&mut self, lines: isize, line_count: usize) {
+        self.scroll = (self.scroll as isize + lines).max(0) as usize;
+        self.move_by(lines, line_count);
+    }
+
+    // Puts the cursor on a line of the view, counting from the top border
+    pub fn click(&mut self, row: u16) {
+        let mut row = (row as usize).saturating_sub(1);
+        self.cursor = self.scroll;
+        for height in &self.heights[self.scroll.min(self.heights.len())..] {
+            if row < *height {
+                return;
+            }
+            row -= height;
+            self.cursor += 1;
+        }
+        self.cursor = self.cursor.min(self.heights.len().saturating_sub(1));
+    }
+
+    // Keeps the cursor on the text and in view in `area`. `widths` are the
+    // display widths of the lines, which decide how many rows they wrap to.
+    pub fn scroll_to_cursor(&mut self, area: Rect, widths: &[usize]) {
+        // A hidden response keeps its place
+        if area.area() == 0 {
+            return;
+        }
+        let height = (area.height as usize).saturating_sub(2).max(1);
+        self.width = (area.width as usize).saturating_sub(2).max(1);
+        self.heights = widths
+            .iter()
+            .map(|width| match self.wrap {
+                true => width.div_ceil(self.width),
+                false => 1,
+            })
+            .map(|rows| rows.max(1))
+            .collect();
+
+        let line_count = widths.len();
+        if line_count == 0 {
+            self.reset();
+            return;
+        }
+        self.cursor = self.cursor.min(line_count.saturating_sub(1));
+        self.scroll = self.scroll.min(line_count.saturating_sub(1));
+        // Don't leave space below the last line
+        let mut below: usize = self.heights[self.scroll.min(line_count)..].iter().sum();
+        while self.scroll > 0 && below + self.heights[self.scroll - 1] <= height {
+            self.scroll -= 1;
+            below += self.heights[self.scroll];
+        }
+        if self.cursor < self.scroll {
+            self.scroll = self.cursor;
+        } else {
+            let mut above: usize = self.heights[self.scroll..=self.cursor].iter().sum();
+            while above > height && self.scroll < self.cursor {
+                above -= self.heights[self.scroll];
+                self.scroll += 1;
+            }
+        }
+    }
+
+    pub fn get_widget<'a>(
+        &self,
+        mut lines: Vec<Spans<'a>>,
+        title: Spans<'a>,
+        colour: Color,
+        cursor_colour: Option<Color>,
+    ) -> Paragraph<'a> {
+        if let Some(cursor_colour) = cursor_colour {
+            if let Some(line) = lines.get_mut(self.cursor) {
+                for span in &mut line.0 {
+                    span.style = span.style.bg(cursor_colour);
+                }
+            }
+        }
+        let scroll_rows: usize = self.heights.iter().take(self.scroll).sum();
+        if self.wrap {
+            lines = lines
+                .into_iter()
+                .flat_map(|line| wrap_line(line, self.width))
+                .collect();
+        }
+
+        Paragraph::new(lines)
+            .block(
+                Block::default()
+                    .title(title)
+                    .borders(Borders::ALL)
+                    .style(Style::default().fg(colour)),
+            )
+            .scroll((scroll_rows as u16, 0))
+    }
+}
+
+// Breaks a line into rows of at most `width` cells
+fn wrap_line(line: Spans<'_>, width: usize) -> Vec<Spans<'_>> {
+    let mut rows = vec![];
+    let mut row: Vec<Span> = vec![];
+    let mut row_width = 0;
+    for span in line.0 {
+        let mut text = String::new();
+        for c in span.content.chars() {
+            let c_width = c.width().unwrap_or(0);
+            if row_width + c_width > width && row_width > 0 {
+                row.push(Span::styled(std::mem::take(&mut text), span.style));
+                rows.push(Spans::from(std::mem::take(&mut row)));
+                row_width = 0;
+            }
+            text.push(c);
+            row_width += c_width;
+        }
+        row.push(Span::styled(text, span.style));
+    }
+    rows.push(Spans::from(row));
+    rows
+}
@@ -0,0 +1,50 @@
+use crate::theme::Theme;
+use tui::{
+    style::{Modifier, Style},
+    text::{Span, Spans},
+    widgets::Paragraph,
+};
+use unicode_width::UnicodeWidthStr;
+
+// One line along the bottom: the mode and what's going on at the left, and
+// as many key hints as fit at the right
+pub fn get_widget<'a>(
+    mode: &'a str,
+    status: Vec<Span<'a>>,
+    hints: &[(String, &'a str)],
+    width: u16,
+    theme: &Theme,
+) -> Paragraph<'a> {
+    let mut spans = vec![
+        Span::styled(
+            format!(" {} ", mode),
+            Style::default()
+                .fg(theme.accent)
+                .add_modifier(Modifier::REVERSED | Modifier::BOLD),
+        ),
+        Span::raw(" "),
+    ];
+    spans.extend(status);
+
+    let mut used = Spans::from(spans.clone()).width();
+    let mut hint_spans = vec![];
+    for (keys, label) in hints {
+        let hint_width = keys.width() + label.width() + 3;
+        if used + hint_width > width as usize {
+            break;
+        }
+        used += hint_width;
+        hint_spans.push(Span::styled(
+            keys.clone(),
+            Style::default().fg(theme.accent),
+        ));
+        hint_spans.push(Span::styled(
+            format!(" {}  ", label),
+            Style::default().fg(theme.dim),
+        ));
+    }
+    spans.push(Span::raw(" ".repeat((width as usize).saturating_sub(used))));
+    spans.extend(hint_spans);
+
+    Paragraph::new(Spans::from(spans)).style(Style::default().fg(theme.text))
+}
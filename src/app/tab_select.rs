@@ -1,31 +1,30 @@
 use tui::{
+    layout::Rect,
     style::{Color, Style},
     symbols,
     text::Spans,
     widgets::{Block, Borders, Tabs},
 };
+use unicode_width::UnicodeWidthStr;
 
 pub struct TabSelect {
     tabs: Vec<String>,
     current_tab: usize,
     title: String,
-    highlighted_color: Color,
+    // The first tab shown, when they don't all fit
+    first: usize,
 }
 
 impl TabSelect {
-    pub fn new(tabs: Vec<String>, title: String, highlighted_color: Color) -> TabSelect {
+    pub fn new(tabs: Vec<String>, title: String) -> TabSelect {
         TabSelect {
             tabs,
             current_tab: 0,
             title,
-            highlighted_color,
+            first: 0,
         }
     }
 
-    //pub fn append_tab(&mut self, title: String) {
-    //self.tabs.push(title);
-    //}
-
     //pub fn remove_tab(&mut self) {
     //if self.tabs.len() > 1 {
     //self.tabs.remove(self.current_tab);
@@ -40,8 +39,63 @@ impl TabSelect {
         self.current_tab += (self.current_tab < self.tabs.len() - 1) as usize;
     }
 
-    pub fn get_widget(&self, color: Color) -> Tabs {
-        let titles = self.tabs.iter().map(|s| Spans::from(s.clone())).collect();
+    // Selects the tab with the given title, returning whether there is one
+    pub fn select(&mut self, title: &str) -> bool {
+        match self
+            .tabs
+            .iter()
+            .position(|tab| tab.eq_ignore_ascii_case(title))
+        {
+            Some(tab) => {
+                self.current_tab = tab;
+                true
+            }
+            None => false,
+        }
+    }
+
+    // Selects the tab with the given title, adding it at the end if there
+    // isn't one
+    pub fn select_or_add(&mut self, title: &str) {
+        if !self.select(title) {
+            self.tabs.push(title.to_string());
+            self.current_tab = self.tabs.len() - 1;
+        }
+    }
+
+    // Selects the tab at `x` columns into the widget, returning whether
+    // one was clicked
+    pub fn click(&mut self, x: u16) -> bool {
+        let widths: Vec<usize> = self.tabs.iter().map(|tab| tab.width()).collect();
+        match tab_at(&widths[self.first..], x) {
+            Some(tab) => {
+                self.current_tab = self.first + tab;
+                true
+            }
+            None => false,
+        }
+    }
+
+    // Scrolls the row so the current tab is in view
+    pub fn scroll_to_current(&mut self, area: Rect) {
+        if area.width == 0 {
+            return;
+        }
+        let width = area.width.saturating_sub(2) as usize;
+        self.first = self.first.min(self.current_tab);
+        let widths: Vec<usize> = self.tabs.iter().map(|tab| tab.width() + 3).collect();
+        while self.first < self.current_tab
+            && widths[self.first..=self.current_tab].iter().sum::<usize>() > width
+        {
+            self.first += 1;
+        }
+    }
+
+    pub fn get_widget(&self, color: Color, highlighted_color: Color) -> Tabs<'_> {
+        let titles = self.tabs[self.first..]
+            .iter()
+            .map(|s| Spans::from(s.clone()))
+            .collect();
         Tabs::new(titles)
             .block(
                 Block::default()
@@ -49,8 +103,8 @@ impl TabSelect {
                     .style(Style::default().fg(color))
                     .borders(Borders::ALL),
             )
-            .select(self.current_tab)
-            .highlight_style(Style::default().fg(self.highlighted_color))
+            .select(self.current_tab - self.first)
+            .highlight_style(Style::default().fg(highlighted_color))
             .divider(symbols::line::VERTICAL)
     }
 
@@ -58,3 +112,17 @@ impl TabSelect {
         self.tabs[self.current_tab].clone()
     }
 }
+
+// Which of a row of bordered tabs with the given title widths is at `x`.
+// Each title is padded by a space on both sides and followed by a divider.
+pub fn tab_at(widths: &[usize], x: u16) -> Option<usize> {
+    let x = (x as usize).checked_sub(1)?;
+    let mut end = 0;
+    for (i, width) in widths.iter().enumerate() {
+        end += width + 3;
+        if x < end {
+            return Some(i);
+        }
+    }
+    None
+}
@@ -1,13 +1,63 @@
+use super::json_text::{self, Token};
+use crate::theme::Theme;
 use tui::{
-    style::{Color, Style},
-    text::Spans,
+    layout::Rect,
+    style::{Color, Modifier, Style},
+    text::{Span, Spans},
     widgets::{Block, Borders, Paragraph},
 };
+use unicode_segmentation::UnicodeSegmentation;
+use unicode_width::UnicodeWidthStr;
 
+const HISTORY_LIMIT: usize = 200;
+
+#[derive(Clone, Copy, PartialEq)]
+pub enum Motion {
+    Left,
+    Right,
+    Up,
+    Down,
+    WordLeft,
+    WordRight,
+    Home,
+    End,
+}
+
+#[derive(Clone, Copy, PartialEq)]
+enum Edit {
+    Insert,
+    Delete,
+    Other,
+}
+
+struct Snapshot {
+    text: String,
+    cursor_pos: usize,
+}
+
+// The cursor and selection anchor are byte offsets into the text, and are
+// always kept on grapheme boundaries
 pub struct TextEntry {
     cursor_pos: usize,
     text: String,
     newlines_allowed: bool,
+    selection_anchor: Option<usize>,
+    // Column to return to when moving up or down through shorter lines
+    preferred_column: Option<usize>,
+    undo_stack: Vec<Snapshot>,
+    redo_stack: Vec<Snapshot>,
+    last_edit: Option<Edit>,
+    // First visible line and display column
+    scroll: (usize, usize),
+    line_numbers: bool,
+    // Bumped by every change to the text
+    revision: usize,
+    // Highlight and validate the text as JSON. The tokens and error are kept
+    // until the text changes, rather than worked out on every draw.
+    json: bool,
+    json_revision: Option<usize>,
+    json_tokens: Vec<Token>,
+    json_error: Option<serde_json::Error>,
 }
 
 // Request text entry
@@ -17,17 +67,101 @@ impl TextEntry {
             cursor_pos: text.len(),
             text,
             newlines_allowed,
+            selection_anchor: None,
+            preferred_column: None,
+            undo_stack: vec![],
+            redo_stack: vec![],
+            last_edit: None,
+            scroll: (0, 0),
+            line_numbers: false,
+            revision: 0,
+            json: false,
+            json_revision: None,
+            json_tokens: vec![],
+            json_error: None,
         }
     }
 
-    pub fn get_widget(&self, colour: Color) -> Paragraph {
-        let lines: Vec<Spans> = self
-            .text
-            .split('\n')
-            .collect::<Vec<&str>>()
-            .iter()
-            .map(|s| Spans::from(s.clone()))
-            .collect();
+    // Only the lines in view are drawn, clipped to the horizontal scroll
+    // offset. Multi-line entries get a gutter marking the cursor's line.
+    pub fn get_widget(&self, colour: Color, area: Rect, theme: &Theme) -> Paragraph<'_> {
+        let selection = self.selection();
+        let (top, left) = self.scroll;
+        let (width, height) = self.view_size(area);
+        let cursor_line = self.text[..self.cursor_pos].matches('\n').count();
+        let error_line = self.json_error.as_ref().map(|e| e.line().saturating_sub(1));
+        let mut lines = vec![];
+        let mut line_start = 0;
+
+        for (number, line) in self.text.split('\n').enumerate() {
+            let line_end = line_start + line.len();
+            if number >= top + height {
+                break;
+            }
+            if number < top {
+                line_start = line_end + 1;
+                continue;
+            }
+
+            let mut spans = vec![];
+            if self.newlines_allowed {
+                let style = if Some(number) == error_line {
+                    Style::default().fg(theme.error)
+                } else if number == cursor_line {
+                    Style::default().fg(theme.accent)
+                } else {
+                    Style::default().fg(theme.dim)
+                };
+                let marker = if Some(number) == error_line {
+                    '✗'
+                } else if number == cursor_line {
+                    '▸'
+                } else {
+                    ' '
+                };
+                let gutter = if self.line_numbers {
+                    format!("{:>w$}{}", number + 1, marker, w = self.gutter_width() - 1)
+                } else {
+                    marker.to_string()
+                };
+                spans.push(Span::styled(gutter, style));
+            }
+
+            // Split the visible part of the line into runs of the same style
+            let mut column = 0;
+            let mut run = String::new();
+            let mut run_style = Style::default();
+            for (i, grapheme) in line.grapheme_indices(true) {
+                let grapheme_width = grapheme.width();
+                column += grapheme_width;
+                if column <= left {
+                    continue;
+                }
+                if column - grapheme_width < left {
+                    // A wide character cut in half by the left edge
+                    run.push_str(&" ".repeat(column - left));
+                    continue;
+                }
+                if column > left + width {
+                    break;
+                }
+                let pos = line_start + i;
+                let mut style = token_style(&self.json_tokens, pos, theme);
+                if selection.is_some_and(|(start, end)| pos >= start && pos < end) {
+                    style = style.add_modifier(Modifier::REVERSED);
+                }
+                if style != run_style && !run.is_empty() {
+                    spans.push(Span::styled(std::mem::take(&mut run), run_style));
+                }
+                run_style = style;
+                run.push_str(grapheme);
+            }
+            spans.push(Span::styled(run, run_style));
+
+            lines.push(Spans::from(spans));
+            line_start = line_end + 1;
+        }
+
         Paragraph::new(lines).block(
             Block::default()
                 .borders(Borders::ALL)
@@ -35,68 +169,646 @@ impl TextEntry {
         )
     }
 
+    // Scrolls just far enough to bring the cursor into view in `area`
+    pub fn scroll_to_cursor(&mut self, area: Rect) {
+        // A hidden editor keeps its place
+        if area.area() == 0 {
+            return;
+        }
+        let (width, height) = self.view_size(area);
+        let (x, y) = (
+            self.column(self.cursor_pos),
+            self.text[..self.cursor_pos].matches('\n').count(),
+        );
+        let (top, left) = &mut self.scroll;
+
+        if y < *top {
+            *top = y;
+        } else if y >= *top + height {
+            *top = y + 1 - height;
+        }
+        if x < *left {
+            *left = x;
+        } else if x >= *left + width {
+            *left = x + 1 - width;
+        }
+    }
+
+    // Turns JSON highlighting on or off, and rechecks the text if it's
+    // changed since the last check
+    pub fn check_json(&mut self, enabled: bool) {
+        self.json = enabled;
+        if !enabled {
+            self.json_revision = None;
+            self.json_tokens.clear();
+            self.json_error = None;
+        } else if self.json_revision != Some(self.revision) {
+            self.json_revision = Some(self.revision);
+            self.json_tokens = json_text::tokens(&self.text);
+            self.json_error = json_text::validate(&self.text).err();
+        }
+    }
+
+    pub fn json_error(&self) -> Option<&serde_json::Error> {
+        self.json_error.as_ref()
+    }
+
+    pub fn toggle_line_numbers(&mut self) {
+        self.line_numbers = !self.line_numbers;
+    }
+
+    fn gutter_width(&self) -> usize {
+        match (self.newlines_allowed, self.line_numbers) {
+            (false, _) => 0,
+            (true, false) => 1,
+            (true, true) => (self.text.matches('\n').count() + 1).to_string().len() + 1,
+        }
+    }
+
+    // Space left for text inside the borders and gutter
+    fn view_size(&self, area: Rect) -> (usize, usize) {
+        let width = (area.width as usize).saturating_sub(2 + self.gutter_width());
+        let height = (area.height as usize).saturating_sub(2);
+        (width.max(1), height.max(1))
+    }
+
     pub fn get_text(&self) -> String {
         self.text.clone()
     }
 
     // Text entry
     pub fn input_char(&mut self, c: char) {
-        // Insert the character at cursor pos
         if c == '\n' && !self.newlines_allowed {
             return;
         }
+        self.insert(&c.to_string(), Edit::Insert);
+    }
 
-        if self.cursor_pos >= self.text.len() {
-            self.text.push(c);
+    pub fn input_str(&mut self, s: &str) {
+        let text = if self.newlines_allowed {
+            s.replace("\r\n", "\n").replace('\r', "\n")
         } else {
-            self.text.insert(self.cursor_pos, c);
+            s.chars().filter(|c| *c != '\n' && *c != '\r').collect()
+        };
+        self.insert(&text, Edit::Other);
+    }
+
+    // Replaces the whole text as one undoable edit, keeping the cursor on
+    // the same line where possible
+    pub fn set_text(&mut self, text: String) {
+        if text == self.text {
+            return;
         }
-        self.cursor_pos += 1;
+        self.save_undo(Edit::Other);
+        let line = self.text[..self.cursor_pos].matches('\n').count();
+        self.text = text;
+        self.selection_anchor = None;
+        self.cursor_pos = self
+            .text
+            .match_indices('\n')
+            .nth(line)
+            .map_or(self.text.len(), |(i, _)| i);
+    }
+
+    // Swaps in another text entirely, e.g. another tab's, along with an
+    // empty undo history so undoing can't bring the old text back
+    pub fn replace_text(&mut self, text: String) {
+        self.cursor_pos = text.len();
+        self.text = text;
+        self.selection_anchor = None;
+        self.preferred_column = None;
+        self.undo_stack.clear();
+        self.redo_stack.clear();
+        self.last_edit = None;
+        self.scroll = (0, 0);
+        self.revision += 1;
     }
 
     pub fn input_tab(&mut self) {
-        self.text.insert_str(self.cursor_pos, "  ");
-        self.cursor_pos += 2;
+        self.insert("  ", Edit::Insert);
     }
 
     pub fn backspace(&mut self) {
-        // Delete character before cursor
-        if self.cursor_pos < 1 {
+        // Delete the selection, or the grapheme before the cursor
+        if self.delete_selection() {
+            return;
+        }
+        if self.cursor_pos == 0 {
             return;
         }
-        self.text.remove(self.cursor_pos - 1);
-        self.cursor_pos -= 1;
+        self.save_undo(Edit::Delete);
+        let start = self.prev_boundary(self.cursor_pos);
+        self.text.replace_range(start..self.cursor_pos, "");
+        self.cursor_pos = start;
     }
 
-    pub fn cursor_left(&mut self) {
-        self.cursor_pos -= (self.cursor_pos > 0) as usize;
+    pub fn delete(&mut self) {
+        // Delete the selection, or the grapheme after the cursor
+        if self.delete_selection() {
+            return;
+        }
+        if self.cursor_pos >= self.text.len() {
+            return;
+        }
+        self.save_undo(Edit::Delete);
+        let end = self.next_boundary(self.cursor_pos);
+        self.text.replace_range(self.cursor_pos..end, "");
     }
 
-    pub fn cursor_right(&mut self) {
-        self.cursor_pos += (self.cursor_pos < self.text.len()) as usize;
+    fn insert(&mut self, s: &str, edit: Edit) {
+        // Replacing a selection is always undone on its own
+        let edit = if self.selection().is_some() {
+            Edit::Other
+        } else {
+            edit
+        };
+        self.save_undo(edit);
+        if let Some((start, end)) = self.selection() {
+            self.text.replace_range(start..end, "");
+            self.cursor_pos = start;
+            self.selection_anchor = None;
+        }
+        self.text.insert_str(self.cursor_pos, s);
+        self.cursor_pos += s.len();
     }
 
-    pub fn cursor_up(&mut self) {
-        // Go back 2 \n's
-        // Go forward the amount of chars you were from the fist \n
+    fn delete_selection(&mut self) -> bool {
+        match self.selection() {
+            Some((start, end)) => {
+                self.save_undo(Edit::Other);
+                self.text.replace_range(start..end, "");
+                self.cursor_pos = start;
+                self.selection_anchor = None;
+                true
+            }
+            None => false,
+        }
     }
+}
 
-    pub fn cursor_down(&mut self) {}
+// Cursor movement
+impl TextEntry {
+    pub fn move_cursor(&mut self, motion: Motion, select: bool) {
+        if select {
+            self.selection_anchor.get_or_insert(self.cursor_pos);
+        } else if let Some((start, end)) = self.selection() {
+            // Moving left or right without shift collapses the selection
+            self.selection_anchor = None;
+            match motion {
+                Motion::Left => return self.move_to(start),
+                Motion::Right => return self.move_to(end),
+                _ => {}
+            }
+        } else {
+            self.selection_anchor = None;
+        }
 
-    pub fn get_cursor_xy(&self) -> (u16, u16) {
-        let chars = self.text.chars().take(self.cursor_pos);
+        match motion {
+            Motion::Left => self.move_to(self.prev_boundary(self.cursor_pos)),
+            Motion::Right => self.move_to(self.next_boundary(self.cursor_pos)),
+            Motion::Up => self.move_vertically(false),
+            Motion::Down => self.move_vertically(true),
+            Motion::WordLeft => self.move_to(self.prev_word(self.cursor_pos)),
+            Motion::WordRight => self.move_to(self.next_word(self.cursor_pos)),
+            Motion::Home => self.move_to(self.line_start(self.cursor_pos)),
+            Motion::End => self.move_to(self.line_end(self.cursor_pos)),
+        }
+    }
+
+    // Moves the cursor to a point inside the borders, e.g. where the mouse
+    // was clicked
+    pub fn click(&mut self, x: u16, y: u16) {
+        let (top, left) = self.scroll;
+        let line = top + y as usize;
+        let column = (x as usize).saturating_sub(self.gutter_width()) + left;
+        let line_start = match line {
+            0 => Some(0),
+            _ => self
+                .text
+                .match_indices('\n')
+                .nth(line - 1)
+                .map(|(i, _)| i + 1),
+        };
+        self.selection_anchor = None;
+        match line_start {
+            Some(start) => self.move_to(self.offset_at_column(start, column)),
+            None => self.move_to(self.text.len()),
+        }
+    }
 
-        let mut x: u16 = 0;
-        chars.clone().for_each(|c| {
-            if c == '\n' {
-                x = 0;
+    // Moves the cursor a number of lines, e.g. for the mouse wheel
+    pub fn scroll_lines(&mut self, lines: isize) {
+        self.selection_anchor = None;
+        let motion = if lines < 0 { Motion::Up } else { Motion::Down };
+        for _ in 0..lines.unsigned_abs() {
+            self.move_cursor(motion, false);
+        }
+    }
+
+    pub fn select_all(&mut self) {
+        self.selection_anchor = Some(0);
+        self.move_to(self.text.len());
+    }
+
+    fn move_to(&mut self, pos: usize) {
+        self.cursor_pos = pos;
+        self.preferred_column = None;
+        self.last_edit = None;
+    }
+
+    fn move_vertically(&mut self, down: bool) {
+        let column = self
+            .preferred_column
+            .unwrap_or_else(|| self.column(self.cursor_pos));
+        let start = self.line_start(self.cursor_pos);
+        let end = self.line_end(self.cursor_pos);
+
+        let target_line = if down {
+            if end >= self.text.len() {
+                None
             } else {
-                x += 1;
+                Some(end + 1)
             }
-        });
+        } else if start == 0 {
+            None
+        } else {
+            Some(self.line_start(start - 1))
+        };
+
+        self.cursor_pos = match target_line {
+            Some(line) => self.offset_at_column(line, column),
+            None if down => self.text.len(),
+            None => 0,
+        };
+        self.preferred_column = Some(column);
+        self.last_edit = None;
+    }
+
+    fn prev_boundary(&self, pos: usize) -> usize {
+        self.text[..pos]
+            .grapheme_indices(true)
+            .next_back()
+            .map_or(0, |(i, _)| i)
+    }
+
+    fn next_boundary(&self, pos: usize) -> usize {
+        self.text[pos..]
+            .graphemes(true)
+            .next()
+            .map_or(pos, |g| pos + g.len())
+    }
+
+    fn prev_word(&self, pos: usize) -> usize {
+        let graphemes: Vec<(usize, &str)> = self.text[..pos].grapheme_indices(true).collect();
+        let mut i = graphemes.len();
+        while i > 0 && !is_word(graphemes[i - 1].1) {
+            i -= 1;
+        }
+        while i > 0 && is_word(graphemes[i - 1].1) {
+            i -= 1;
+        }
+        graphemes.get(i).map_or(pos, |(offset, _)| *offset)
+    }
+
+    fn next_word(&self, pos: usize) -> usize {
+        let mut graphemes = self.text[pos..].grapheme_indices(true).peekable();
+        while graphemes.next_if(|(_, g)| !is_word(g)).is_some() {}
+        while graphemes.next_if(|(_, g)| is_word(g)).is_some() {}
+        graphemes.peek().map_or(self.text.len(), |(i, _)| pos + i)
+    }
+
+    fn line_start(&self, pos: usize) -> usize {
+        self.text[..pos].rfind('\n').map_or(0, |i| i + 1)
+    }
+
+    fn line_end(&self, pos: usize) -> usize {
+        self.text[pos..]
+            .find('\n')
+            .map_or(self.text.len(), |i| pos + i)
+    }
+
+    // Display width of the line up to `pos`
+    fn column(&self, pos: usize) -> usize {
+        self.text[self.line_start(pos)..pos].width()
+    }
+
+    // The offset on the line starting at `line` closest to `column`
+    fn offset_at_column(&self, line: usize, column: usize) -> usize {
+        let end = self.line_end(line);
+        let mut width = 0;
+        for (i, grapheme) in self.text[line..end].grapheme_indices(true) {
+            width += grapheme.width();
+            if width > column {
+                return line + i;
+            }
+        }
+        end
+    }
+
+    // Cursor position relative to the scrolled view, after the gutter
+    pub fn get_cursor_xy(&self) -> (u16, u16) {
+        let (top, left) = self.scroll;
+        let x = self.column(self.cursor_pos).saturating_sub(left) + self.gutter_width();
+        let y = self.text[..self.cursor_pos]
+            .matches('\n')
+            .count()
+            .saturating_sub(top);
+        (x as u16, y as u16)
+    }
+}
+
+// Selection and clipboard
+impl TextEntry {
+    fn selection(&self) -> Option<(usize, usize)> {
+        match self.selection_anchor {
+            Some(anchor) if anchor != self.cursor_pos => {
+                Some((anchor.min(self.cursor_pos), anchor.max(self.cursor_pos)))
+            }
+            _ => None,
+        }
+    }
+
+    pub fn selected_text(&self) -> Option<String> {
+        self.selection()
+            .map(|(start, end)| self.text[start..end].to_string())
+    }
+
+    pub fn cut(&mut self) -> Option<String> {
+        let text = self.selected_text();
+        self.delete_selection();
+        text
+    }
+}
+
+// Undo history
+impl TextEntry {
+    // Runs of typing or deleting are undone together. Every edit saves
+    // first, so this is where the text's revision moves on.
+    fn save_undo(&mut self, edit: Edit) {
+        self.revision += 1;
+        if edit == Edit::Other || self.last_edit != Some(edit) {
+            self.undo_stack.push(Snapshot {
+                text: self.text.clone(),
+                cursor_pos: self.cursor_pos,
+            });
+            if self.undo_stack.len() > HISTORY_LIMIT {
+                self.undo_stack.remove(0);
+            }
+        }
+        self.redo_stack.clear();
+        self.last_edit = Some(edit);
+        self.preferred_column = None;
+    }
+
+    pub fn undo(&mut self) {
+        if let Some(snapshot) = self.undo_stack.pop() {
+            let current = self.restore(snapshot);
+            self.redo_stack.push(current);
+        }
+    }
+
+    pub fn redo(&mut self) {
+        if let Some(snapshot) = self.redo_stack.pop() {
+            let current = self.restore(snapshot);
+            self.undo_stack.push(current);
+        }
+    }
+
+    fn restore(&mut self, snapshot: Snapshot) -> Snapshot {
+        let current = Snapshot {
+            text: std::mem::replace(&mut self.text, snapshot.text),
+            cursor_pos: self.cursor_pos,
+        };
+        self.cursor_pos = snapshot.cursor_pos;
+        self.selection_anchor = None;
+        self.preferred_column = None;
+        self.last_edit = None;
+        self.revision += 1;
+        current
+    }
+}
+
+fn token_style(tokens: &[Token], pos: usize, theme: &Theme) -> Style {
+    let i = tokens.partition_point(|token| token.range.end <= pos);
+    tokens
+        .get(i)
+        .map_or_else(Style::default, |token| json_text::style(token.kind, theme))
+}
+
+fn is_word(grapheme: &str) -> bool {
+    grapheme.chars().all(|c| c.is_alphanumeric() || c == '_')
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entry(text: &str) -> TextEntry {
+        TextEntry::new(text.to_string(), true)
+    }
+
+    fn moved(entry: &mut TextEntry, motion: Motion, times: usize) -> usize {
+        for _ in 0..times {
+            entry.move_cursor(motion, false);
+        }
+        entry.cursor_pos
+    }
+
+    #[test]
+    fn edits_whole_graphemes() {
+        // e + combining accent, a family emoji joined with ZWJs, and a flag
+        let mut entry = entry("ae\u{301}👨‍👩‍👧🇳🇿");
+        entry.backspace();
+        assert_eq!(entry.get_text(), "ae\u{301}👨‍👩‍👧");
+        entry.backspace();
+        assert_eq!(entry.get_text(), "ae\u{301}");
+        entry.move_cursor(Motion::Left, false);
+        assert_eq!(entry.cursor_pos, 1);
+        entry.input_char('ß');
+        assert_eq!(entry.get_text(), "aße\u{301}");
+        entry.delete();
+        assert_eq!(entry.get_text(), "aß");
+        entry.delete();
+        entry.move_cursor(Motion::Home, false);
+        entry.backspace();
+        assert_eq!(entry.get_text(), "aß");
+        entry.delete();
+        entry.delete();
+        entry.delete();
+        assert_eq!(entry.get_text(), "");
+    }
+
+    #[test]
+    fn counts_wide_characters_as_two_columns() {
+        let mut entry = entry("日本語");
+        assert_eq!(entry.get_cursor_xy(), (7, 0));
+        entry.move_cursor(Motion::Left, false);
+        assert_eq!(entry.get_cursor_xy(), (5, 0));
+        assert_eq!(entry.cursor_pos, "日本".len());
+    }
+
+    #[test]
+    fn keeps_the_column_through_shorter_lines() {
+        let mut entry = entry("日本語です\nab\n\nxyz123");
+        entry.move_cursor(Motion::Home, false);
+        moved(&mut entry, Motion::Up, 3);
+        moved(&mut entry, Motion::Right, 2);
+        assert_eq!(entry.cursor_pos, "日本".len());
+
+        assert_eq!(moved(&mut entry, Motion::Down, 1), "日本語です\nab".len());
+        assert_eq!(moved(&mut entry, Motion::Down, 1), "日本語です\nab\n".len());
+        assert_eq!(
+            moved(&mut entry, Motion::Down, 1),
+            "日本語です\nab\n\nxyz1".len()
+        );
+        assert_eq!(moved(&mut entry, Motion::Up, 3), "日本".len());
+        // A column in the middle of a wide character lands before it
+        moved(&mut entry, Motion::Down, 3);
+        moved(&mut entry, Motion::Right, 1);
+        assert_eq!(moved(&mut entry, Motion::Up, 3), "日本".len());
+    }
+
+    #[test]
+    fn moves_past_the_first_and_last_lines() {
+        let mut entry = entry("abc\ndef");
+        assert_eq!(moved(&mut entry, Motion::Down, 1), 7);
+        assert_eq!(moved(&mut entry, Motion::Up, 2), 0);
+    }
+
+    #[test]
+    fn moves_by_word() {
+        let mut entry = entry("foo bar_baz, qux");
+        entry.move_cursor(Motion::Home, false);
+        assert_eq!(moved(&mut entry, Motion::WordRight, 1), 3);
+        assert_eq!(moved(&mut entry, Motion::WordRight, 1), 11);
+        assert_eq!(moved(&mut entry, Motion::WordRight, 2), 16);
+        assert_eq!(moved(&mut entry, Motion::WordLeft, 1), 13);
+        assert_eq!(moved(&mut entry, Motion::WordLeft, 1), 4);
+        assert_eq!(moved(&mut entry, Motion::WordLeft, 2), 0);
+    }
+
+    #[test]
+    fn cuts_and_pastes_selections() {
+        let mut entry = entry("hello wörld");
+        entry.move_cursor(Motion::WordLeft, true);
+        assert_eq!(entry.selected_text().as_deref(), Some("wörld"));
+        assert_eq!(entry.cut().as_deref(), Some("wörld"));
+        assert_eq!(entry.get_text(), "hello ");
+        assert_eq!(entry.cut(), None);
+
+        entry.move_cursor(Motion::Home, false);
+        entry.input_str("wörld\r\n");
+        assert_eq!(entry.get_text(), "wörld\nhello ");
+
+        // Typing replaces the selection, and moving collapses it
+        entry.select_all();
+        entry.move_cursor(Motion::Left, false);
+        assert_eq!(entry.cursor_pos, 0);
+        entry.move_cursor(Motion::Right, true);
+        entry.move_cursor(Motion::Down, true);
+        assert_eq!(entry.selected_text().as_deref(), Some("wörld\nh"));
+        entry.input_char('W');
+        assert_eq!(entry.get_text(), "Wello ");
+    }
+
+    #[test]
+    fn single_line_entries_drop_newlines() {
+        let mut entry = TextEntry::new(String::new(), false);
+        entry.input_char('\n');
+        entry.input_str("a\r\nb\n");
+        assert_eq!(entry.get_text(), "ab");
+    }
+
+    #[test]
+    fn undoes_runs_of_typing_and_deleting() {
+        let mut entry = entry("");
+        for c in "abc".chars() {
+            entry.input_char(c);
+        }
+        entry.move_cursor(Motion::Left, false);
+        entry.input_char('X');
+        entry.backspace();
+        entry.backspace();
+        assert_eq!(entry.get_text(), "ac");
+
+        entry.undo();
+        assert_eq!(entry.get_text(), "abXc");
+        entry.undo();
+        assert_eq!(entry.get_text(), "abc");
+        entry.undo();
+        assert_eq!(entry.get_text(), "");
+        entry.undo();
+        assert_eq!(entry.get_text(), "");
+
+        entry.redo();
+        entry.redo();
+        assert_eq!(entry.get_text(), "abXc");
+        assert_eq!(entry.cursor_pos, 3);
+        // A new edit drops what could be redone
+        entry.input_char('!');
+        entry.redo();
+        assert_eq!(entry.get_text(), "abX!c");
+    }
+
+    #[test]
+    fn keeps_a_limited_history() {
+        let mut entry = entry("");
+        for _ in 0..HISTORY_LIMIT + 10 {
+            entry.input_str("x");
+        }
+        for _ in 0..HISTORY_LIMIT + 10 {
+            entry.undo();
+        }
+        assert_eq!(entry.get_text(), "x".repeat(10));
+        for _ in 0..HISTORY_LIMIT {
+            entry.redo();
+        }
+        assert_eq!(entry.get_text().len(), HISTORY_LIMIT + 10);
+    }
+
+    #[test]
+    fn rechecks_json_after_edits() {
+        let mut entry = entry("{\"a\": 1}");
+        entry.check_json(true);
+        assert!(entry.json_error().is_none());
+        assert_eq!(entry.json_tokens.len(), 6);
+        let checked = entry.json_revision;
+        // Nothing changed, so nothing is worked out again
+        entry.move_cursor(Motion::Home, false);
+        entry.check_json(true);
+        assert_eq!(entry.json_revision, checked);
+
+        entry.input_char('x');
+        entry.check_json(true);
+        assert!(entry.json_error().is_some());
+        entry.undo();
+        entry.check_json(true);
+        assert!(entry.json_error().is_none());
+        entry.redo();
+        entry.check_json(true);
+        assert!(entry.json_error().is_some());
+        entry.replace_text("[1]".to_string());
+        entry.check_json(true);
+        assert!(entry.json_error().is_none());
+        assert_eq!(entry.json_tokens.len(), 3);
+
+        entry.input_char(',');
+        entry.check_json(false);
+        assert!(entry.json_error().is_none());
+        assert!(entry.json_tokens.is_empty());
+        entry.check_json(true);
+        assert!(entry.json_error().is_some());
+    }
 
-        let y = chars.filter(|c| c == &'\n').count() as u16;
-        // x = number of chars after last \n
-        (x, y)
+    #[test]
+    fn replacing_the_text_clears_the_history() {
+        let mut entry = entry("one");
+        entry.input_char('!');
+        entry.replace_text("two".to_string());
+        entry.undo();
+        assert_eq!(entry.get_text(), "two");
+        entry.set_text("three".to_string());
+        entry.undo();
+        assert_eq!(entry.get_text(), "two");
     }
 }
@@ -0,0 +1,75 @@
+use http_request_tool::timing::Timings;
+use tui::{
+    style::{Color, Style},
+    text::{Span, Spans},
+    widgets::{Block, Borders, Paragraph},
+};
+
+const LABEL_WIDTH: usize = 9;
+const VALUE_WIDTH: usize = 9;
+const PHASE_COLORS: [Color; 5] = [
+    Color::Cyan,
+    Color::Green,
+    Color::Magenta,
+    Color::Yellow,
+    Color::Blue,
+];
+
+// One row per phase, each bar starting where the phase started
+pub fn get_widget(
+    timings: &Timings,
+    title: String,
+    width: u16,
+    colour: Color,
+) -> Paragraph<'static> {
+    let bar_width = (width as usize).saturating_sub(LABEL_WIDTH + VALUE_WIDTH + 2);
+    let total = timings.total().as_secs_f64();
+
+    let mut lines: Vec<Spans> = timings
+        .phases()
+        .iter()
+        .zip(PHASE_COLORS.iter())
+        .map(|((name, start, duration), color)| {
+            let (start, duration) = (start.as_secs_f64(), duration.as_secs_f64());
+            let (offset, length) = if total > 0.0 {
+                // The probe's phases can take longer than the request did
+                let position =
+                    |time: f64| ((time / total).min(1.0) * bar_width as f64).round() as usize;
+                let offset = position(start);
+                let end = position(start + duration);
+                (offset, (end - offset).max((duration > 0.0) as usize))
+            } else {
+                (0, 0)
+            };
+
+            Spans::from(vec![
+                Span::raw(format!("{:<1$}", name, LABEL_WIDTH)),
+                Span::raw(" ".repeat(offset.min(bar_width))),
+                Span::styled(
+                    "█".repeat(length.min(bar_width - offset.min(bar_width))),
+                    Style::default().fg(*color),
+                ),
+                Span::raw(" ".repeat(bar_width.saturating_sub(offset + length))),
+                Span::raw(format!("{:>1$}", format_ms(duration), VALUE_WIDTH)),
+            ])
+        })
+        .collect();
+    lines.push(Spans::from(format!(
+        "{:<2$}{:>3$}",
+        "Total",
+        format_ms(total),
+        LABEL_WIDTH,
+        bar_width + VALUE_WIDTH
+    )));
+
+    Paragraph::new(lines).block(
+        Block::default()
+            .title(title)
+            .borders(Borders::ALL)
+            .style(Style::default().fg(colour)),
+    )
+}
+
+fn format_ms(seconds: f64) -> String {
+    format!("{:.1}ms", seconds * 1000.0)
+}
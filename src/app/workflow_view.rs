@@ -0,0 +1,193 @@
+use crate::theme::Theme;
+use http_request_tool::runner::RequestResult;
+use http_request_tool::workflow::{Dependency, StepStatus, WorkflowGraph, WorkflowRun};
+use petgraph::graph::NodeIndex;
+use std::sync::mpsc::Receiver;
+use std::sync::Arc;
+use tui::{
+    style::{Color, Modifier, Style},
+    text::{Span, Spans},
+    widgets::{Block, Borders, Paragraph},
+};
+
+pub enum RunEvent {
+    Step(NodeIndex, StepStatus, String),
+    Finished(WorkflowRun),
+}
+
+pub struct ActiveRun {
+    graph: Arc<WorkflowGraph>,
+    statuses: Vec<StepStatus>,
+    summaries: Vec<String>,
+    events: Receiver<RunEvent>,
+    finished: bool,
+}
+
+impl ActiveRun {
+    pub fn new(graph: Arc<WorkflowGraph>, events: Receiver<RunEvent>) -> ActiveRun {
+        let steps = graph.graph.node_count();
+        ActiveRun {
+            graph,
+            statuses: vec![StepStatus::Pending; steps],
+            summaries: vec![String::new(); steps],
+            events,
+            finished: false,
+        }
+    }
+
+    pub fn is_finished(&self) -> bool {
+        self.finished
+    }
+
+    // Applies any progress reported by the runner thread, returning the
+    // final state of the run once it's over
+    pub fn update(&mut self) -> Option<WorkflowRun> {
+        while let Ok(event) = self.events.try_recv() {
+            match event {
+                RunEvent::Step(node, status, summary) => {
+                    self.statuses[node.index()] = status;
+                    self.summaries[node.index()] = summary;
+                }
+                RunEvent::Finished(run) => {
+                    self.finished = true;
+                    return Some(run);
+                }
+            }
+        }
+        None
+    }
+
+    // One line per step, grouped into layers that can run in parallel
+    fn lines(&self, theme: &Theme) -> Vec<Spans<'static>> {
+        let graph = &self.graph;
+        let mut lines = vec![Spans::from(Span::styled(
+            format!("Workflow {}", graph.name),
+            Style::default().add_modifier(Modifier::BOLD),
+        ))];
+
+        for (layer, nodes) in graph.layers().iter().enumerate() {
+            lines.push(Spans::from(format!("Stage {}", layer + 1)));
+            for node in nodes {
+                let status = self.statuses[node.index()];
+                let (mark, color) = match status {
+                    StepStatus::Pending => ("○", theme.text),
+                    StepStatus::Running => ("◐", theme.accent),
+                    StepStatus::Passed => ("✓", theme.success),
+                    StepStatus::Failed => ("✗", theme.error),
+                    StepStatus::Skipped => ("-", theme.dim),
+                };
+                let dependencies: Vec<String> = graph
+                    .dependencies(*node)
+                    .map(|(before, dependency)| match dependency {
+                        Dependency::Data(variables) => {
+                            format!("{} {{{}}}", graph.graph[before].id, variables.join(", "))
+                        }
+                        Dependency::Order => graph.graph[before].id.clone(),
+                    })
+                    .collect();
+
+                let mut spans = vec![
+                    Span::styled(format!("  {} ", mark), Style::default().fg(color)),
+                    Span::raw(format!("{:<20}", graph.graph[*node].id)),
+                    Span::raw(format!("{:<24}", self.summaries[node.index()])),
+                ];
+                if !dependencies.is_empty() {
+                    spans.push(Span::styled(
+                        format!("← {}", dependencies.join(", ")),
+                        Style::default().fg(theme.dim),
+                    ));
+                }
+                lines.push(Spans::from(spans));
+            }
+        }
+
+        if self.finished {
+            lines.push(Spans::from(""));
+            lines.push(Spans::from("Finished"));
+        }
+        lines
+    }
+}
+
+pub struct WorkflowView {
+    pub visible: bool,
+    pub names: Vec<String>,
+    pub selected: usize,
+    pub error: Option<String>,
+    pub run: Option<ActiveRun>,
+}
+
+impl WorkflowView {
+    pub fn new(names: Vec<String>) -> WorkflowView {
+        WorkflowView {
+            visible: false,
+            names,
+            selected: 0,
+            error: None,
+            run: None,
+        }
+    }
+
+    pub fn move_up(&mut self) {
+        self.selected -= (self.selected > 0) as usize;
+    }
+
+    pub fn move_down(&mut self) {
+        self.selected += (self.selected + 1 < self.names.len()) as usize;
+    }
+
+    pub fn get_widget(&self, hints: &str, colour: Color, theme: &Theme) -> Paragraph<'_> {
+        let mut lines = vec![];
+        if self.names.is_empty() {
+            lines.push(Spans::from("No workflows in collection.json"));
+        }
+        for (i, name) in self.names.iter().enumerate() {
+            let style = if i == self.selected {
+                Style::default().fg(theme.highlight)
+            } else {
+                Style::default()
+            };
+            lines.push(Spans::from(Span::styled(name.clone(), style)));
+        }
+        lines.push(Spans::from(""));
+
+        if let Some(error) = &self.error {
+            lines.push(Spans::from(Span::styled(
+                error.clone(),
+                Style::default().fg(theme.error),
+            )));
+        }
+        if let Some(run) = &self.run {
+            lines.extend(run.lines(theme));
+        }
+
+        Paragraph::new(lines).block(
+            Block::default()
+                .title(match hints {
+                    "" => "Workflows".to_string(),
+                    hints => format!("Workflows ({})", hints),
+                })
+                .borders(Borders::ALL)
+                .style(Style::default().fg(colour)),
+        )
+    }
+}
+
+pub fn summarize(result: &RequestResult) -> String {
+    if result.skipped {
+        return "skipped".to_string();
+    }
+    if let Some(error) = &result.error {
+        return error.clone();
+    }
+    let failed = result.assertions.iter().filter(|a| !a.passed).count();
+    let status = result
+        .status
+        .map(|status| status.to_string())
+        .unwrap_or_default();
+    if failed > 0 {
+        format!("{} · {} failed", status, failed)
+    } else {
+        format!("{} · {}ms", status, result.duration_ms)
+    }
+}
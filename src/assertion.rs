@@ -0,0 +1,445 @@
+use crate::json_path;
+use crate::request::ResponseRecord;
+use regex::Regex;
+use serde::{de, Deserialize, Deserializer, Serialize, Serializer};
+use serde_json::Value;
+use std::fmt;
+use std::str::FromStr;
+
+// Assertions are written one per line, for example:
+//
+//   status == 200
+//   status in 200..299        (both ends included)
+//   header Content-Type exists
+//   header Content-Type matches ^application/json
+//   json $.user.id == 42
+//   json $.user.name matches ^[A-Z]
+//   json $.items is array
+//   body contains "ok"
+//   time < 500ms
+#[derive(Clone)]
+pub enum Assertion {
+    StatusEquals(u16),
+    StatusInRange(u16, u16),
+    HeaderPresent(String),
+    HeaderMatches(String, Regex),
+    JsonEquals(String, Value),
+    JsonMatches(String, Regex),
+    JsonType(String, JsonType),
+    BodyContains(String),
+    TimeUnder(u64),
+}
+
+#[derive(Clone, Copy, PartialEq)]
+pub enum JsonType {
+    Null,
+    Boolean,
+    Number,
+    String,
+    Array,
+    Object,
+}
+
+#[derive(Clone, Serialize, Deserialize)]
+pub struct AssertionResult {
+    pub description: String,
+    pub passed: bool,
+    pub message: String,
+}
+
+impl Assertion {
+    pub fn evaluate(&self, response: &ResponseRecord) -> AssertionResult {
+        let outcome = match self {
+            Assertion::StatusEquals(expected) => check(
+                response.status == *expected,
+                format!("status was {}", response.status),
+            ),
+            Assertion::StatusInRange(low, high) => check(
+                (*low..=*high).contains(&response.status),
+                format!("status was {}", response.status),
+            ),
+            Assertion::HeaderPresent(name) => match response.header(name) {
+                Some(value) => Ok(format!("{}: {}", name, value)),
+                None => Err(format!("header {} is missing", name)),
+            },
+            Assertion::HeaderMatches(name, regex) => match response.header(name) {
+                Some(value) => check(regex.is_match(value), format!("{}: {}", name, value)),
+                None => Err(format!("header {} is missing", name)),
+            },
+            Assertion::JsonEquals(path, expected) => select_json(response, path)
+                .and_then(|value| check(value == *expected, format!("{} was {}", path, value))),
+            Assertion::JsonMatches(path, regex) => select_json(response, path).and_then(|value| {
+                let text = match &value {
+                    Value::String(s) => s.clone(),
+                    other => other.to_string(),
+                };
+                check(regex.is_match(&text), format!("{} was {}", path, value))
+            }),
+            Assertion::JsonType(path, expected) => select_json(response, path).and_then(|value| {
+                let actual = JsonType::of(&value);
+                check(actual == *expected, format!("{} is {}", path, actual))
+            }),
+            Assertion::BodyContains(text) => check(
+                response.body.contains(text.as_str()),
+                format!("body is {} bytes", response.body.len()),
+            ),
+            Assertion::TimeUnder(limit) => {
+                let elapsed = response.timings.total().as_millis();
+                check(elapsed < *limit as u128, format!("took {}ms", elapsed))
+            }
+        };
+
+        let (passed, message) = match outcome {
+            Ok(message) => (true, message),
+            Err(message) => (false, message),
+        };
+        AssertionResult {
+            description: self.to_string(),
+            passed,
+            message,
+        }
+    }
+}
+
+fn check(passed: bool, message: String) -> Result<String, String> {
+    if passed {
+        Ok(message)
+    } else {
+        Err(message)
+    }
+}
+
+fn select_json(response: &ResponseRecord, path: &str) -> Result<Value, String> {
+    let body: Value =
+        serde_json::from_str(&response.body).map_err(|e| format!("body is not JSON: {}", e))?;
+    json_path::select(&body, path)?
+        .cloned()
+        .ok_or_else(|| format!("{} not found", path))
+}
+
+impl JsonType {
+    fn of(value: &Value) -> JsonType {
+        match value {
+            Value::Null => JsonType::Null,
+            Value::Bool(_) => JsonType::Boolean,
+            Value::Number(_) => JsonType::Number,
+            Value::String(_) => JsonType::String,
+            Value::Array(_) => JsonType::Array,
+            Value::Object(_) => JsonType::Object,
+        }
+    }
+}
+
+impl fmt::Display for JsonType {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let name = match self {
+            JsonType::Null => "null",
+            JsonType::Boolean => "boolean",
+            JsonType::Number => "number",
+            JsonType::String => "string",
+            JsonType::Array => "array",
+            JsonType::Object => "object",
+        };
+        write!(f, "{}", name)
+    }
+}
+
+impl FromStr for JsonType {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<JsonType, String> {
+        match s {
+            "null" => Ok(JsonType::Null),
+            "boolean" | "bool" => Ok(JsonType::Boolean),
+            "number" => Ok(JsonType::Number),
+            "string" => Ok(JsonType::String),
+            "array" => Ok(JsonType::Array),
+            "object" => Ok(JsonType::Object),
+            other => Err(format!("unknown JSON type: {}", other)),
+        }
+    }
+}
+
+impl fmt::Display for Assertion {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Assertion::StatusEquals(status) => write!(f, "status == {}", status),
+            Assertion::StatusInRange(low, high) => write!(f, "status in {}..{}", low, high),
+            Assertion::HeaderPresent(name) => write!(f, "header {} exists", name),
+            Assertion::HeaderMatches(name, regex) => write!(f, "header {} matches {}", name, regex),
+            Assertion::JsonEquals(path, value) => write!(f, "json {} == {}", path, value),
+            Assertion::JsonMatches(path, regex) => write!(f, "json {} matches {}", path, regex),
+            Assertion::JsonType(path, json_type) => write!(f, "json {} is {}", path, json_type),
+            Assertion::BodyContains(text) => {
+                write!(f, "body contains {}", Value::from(text.as_str()))
+            }
+            Assertion::TimeUnder(limit) => write!(f, "time < {}ms", limit),
+        }
+    }
+}
+
+impl FromStr for Assertion {
+    type Err = String;
+
+    fn from_str(line: &str) -> Result<Assertion, String> {
+        let invalid = || format!("invalid assertion: {}", line);
+        let (subject, rest) = split_word(line);
+
+        match subject {
+            "status" => {
+                let (operator, value) = split_word(rest);
+                match operator {
+                    "==" => Ok(Assertion::StatusEquals(
+                        value.parse().map_err(|_| invalid())?,
+                    )),
+                    "in" => {
+                        let mut bounds = value.splitn(2, "..");
+                        let low = bounds.next().and_then(|b| b.trim().parse().ok());
+                        let high = bounds.next().and_then(|b| b.trim().parse().ok());
+                        match (low, high) {
+                            (Some(low), Some(high)) => Ok(Assertion::StatusInRange(low, high)),
+                            _ => Err(format!(
+                                "{} (expected a range like 200..299, which includes both ends)",
+                                invalid()
+                            )),
+                        }
+                    }
+                    _ => Err(invalid()),
+                }
+            }
+            "header" => {
+                let (name, rest) = split_word(rest);
+                let (operator, value) = split_word(rest);
+                match operator {
+                    "exists" if value.is_empty() => Ok(Assertion::HeaderPresent(name.to_string())),
+                    "matches" => Ok(Assertion::HeaderMatches(name.to_string(), regex(value)?)),
+                    _ => Err(invalid()),
+                }
+            }
+            "json" => {
+                let (path, rest) = split_path(rest);
+                let (operator, value) = split_word(rest);
+                let path = path.to_string();
+                match operator {
+                    "==" => Ok(Assertion::JsonEquals(
+                        path,
+                        serde_json::from_str(value).map_err(|e| format!("{}: {}", invalid(), e))?,
+                    )),
+                    "matches" => Ok(Assertion::JsonMatches(path, regex(value)?)),
+                    "is" => Ok(Assertion::JsonType(path, value.parse()?)),
+                    _ => Err(invalid()),
+                }
+            }
+            "body" => {
+                let (operator, value) = split_word(rest);
+                match operator {
+                    "contains" if !value.is_empty() => Ok(Assertion::BodyContains(unquote(value))),
+                    _ => Err(invalid()),
+                }
+            }
+            "time" => {
+                let (operator, value) = split_word(rest);
+                let value = value.strip_suffix("ms").unwrap_or(value);
+                match operator {
+                    "<" => Ok(Assertion::TimeUnder(value.parse().map_err(|_| invalid())?)),
+                    _ => Err(invalid()),
+                }
+            }
+            _ => Err(invalid()),
+        }
+    }
+}
+
+// Collections store assertions in the same form they're written in the TUI
+impl Serialize for Assertion {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(self)
+    }
+}
+
+impl<'de> Deserialize<'de> for Assertion {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Assertion, D::Error> {
+        String::deserialize(deserializer)?
+            .parse()
+            .map_err(de::Error::custom)
+    }
+}
+
+// Parses every non-empty line that isn't a `#` comment
+pub fn parse_lines(text: &str) -> Vec<Result<Assertion, String>> {
+    text.lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty() && !line.starts_with('#'))
+        .map(str::parse)
+        .collect()
+}
+
+fn split_word(s: &str) -> (&str, &str) {
+    let s = s.trim();
+    match s.find(char::is_whitespace) {
+        Some(i) => (&s[..i], s[i..].trim()),
+        None => (s, ""),
+    }
+}
+
+// Like `split_word`, but whitespace inside brackets, e.g. in
+// `$['first name']`, doesn't end the path
+fn split_path(s: &str) -> (&str, &str) {
+    let s = s.trim();
+    let mut quote = None;
+    let mut depth = 0;
+    for (i, c) in s.char_indices() {
+        match (quote, c) {
+            (Some(open), c) if c == open => quote = None,
+            (Some(_), _) => {}
+            (None, '\'' | '"') if depth > 0 => quote = Some(c),
+            (None, '[') => depth += 1,
+            (None, ']') if depth > 0 => depth -= 1,
+            (None, c) if c.is_whitespace() && depth == 0 => return (&s[..i], s[i..].trim()),
+            _ => {}
+        }
+    }
+    (s, "")
+}
+
+fn unquote(s: &str) -> String {
+    match serde_json::from_str::<String>(s) {
+        Ok(unquoted) => unquoted,
+        Err(_) => s.to_string(),
+    }
+}
+
+fn regex(pattern: &str) -> Result<Regex, String> {
+    Regex::new(pattern).map_err(|e| e.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::timing::Timings;
+    use std::time::Duration;
+
+    fn response() -> ResponseRecord {
+        ResponseRecord {
+            status: 201,
+            headers: vec![("Content-Type".to_string(), "application/json".to_string())],
+            body: r#"{"user":{"id":42,"name":"Ann"},"items":[1,2]}"#.to_string(),
+            timings: Timings {
+                first_byte: Duration::from_millis(120),
+                download: Duration::from_millis(30),
+                ..Timings::default()
+            },
+        }
+    }
+
+    fn evaluate(line: &str) -> AssertionResult {
+        line.parse::<Assertion>().unwrap().evaluate(&response())
+    }
+
+    #[test]
+    fn parses_lines_skipping_comments() {
+        let parsed = parse_lines("status == 200\n\n# a comment\n  time < 500ms  \nstatus is 200");
+        assert_eq!(parsed.len(), 3);
+        assert!(parsed[0].is_ok());
+        assert!(parsed[1].is_ok());
+        assert_eq!(
+            parsed[2].as_ref().err().unwrap(),
+            "invalid assertion: status is 200"
+        );
+    }
+
+    #[test]
+    fn rejects_invalid_assertions() {
+        for line in [
+            "status == ok",
+            "status in 200",
+            "header Content-Type",
+            "json $.id == {",
+            "json $.id is thing",
+            "body contains",
+            "time > 5ms",
+            "size < 10",
+        ] {
+            assert!(line.parse::<Assertion>().is_err(), "{}", line);
+        }
+    }
+
+    #[test]
+    fn displays_as_written() {
+        for line in [
+            "status == 200",
+            "status in 200..299",
+            "header Content-Type exists",
+            "header Content-Type matches ^application/json",
+            "json $.user.id == 42",
+            "json $.items is array",
+            "body contains \"ok\"",
+            "time < 500ms",
+        ] {
+            assert_eq!(line.parse::<Assertion>().unwrap().to_string(), line);
+        }
+    }
+
+    #[test]
+    fn checks_status() {
+        assert!(!evaluate("status == 200").passed);
+        assert_eq!(evaluate("status == 200").message, "status was 201");
+        assert!(evaluate("status in 200..299").passed);
+    }
+
+    #[test]
+    fn checks_headers_ignoring_case() {
+        assert!(evaluate("header content-type exists").passed);
+        assert!(evaluate("header Content-Type matches json$").passed);
+        assert!(!evaluate("header Location exists").passed);
+    }
+
+    #[test]
+    fn checks_json() {
+        assert!(evaluate("json $.user.id == 42").passed);
+        assert!(!evaluate("json $.user.id == \"42\"").passed);
+        assert!(evaluate("json $.user.name matches ^A").passed);
+        assert!(evaluate("json $.items is array").passed);
+        assert!(evaluate("json $.items[1] is number").passed);
+        let missing = evaluate("json $.user.email is string");
+        assert!(!missing.passed);
+        assert_eq!(missing.message, "$.user.email not found");
+    }
+
+    #[test]
+    fn reads_json_paths_with_spaces() {
+        let response = ResponseRecord {
+            body: r#"{"first name":"Ann","a]b":{"x y":[1]}}"#.to_string(),
+            ..response()
+        };
+        for line in [
+            r#"json $['first name'] == "Ann""#,
+            r#"json $["first name"] matches ^A"#,
+            r#"json $['a]b']["x y"][0] is number"#,
+        ] {
+            let assertion: Assertion = line.parse().unwrap();
+            assert_eq!(assertion.to_string(), line);
+            assert!(assertion.evaluate(&response).passed, "{}", line);
+        }
+    }
+
+    #[test]
+    fn status_ranges_include_both_ends() {
+        assert!(evaluate("status in 201..201").passed);
+        assert!(evaluate("status in 200..201").passed);
+        assert!(!evaluate("status in 202..299").passed);
+        assert!("status in 200-299"
+            .parse::<Assertion>()
+            .err()
+            .unwrap()
+            .contains("includes both ends"));
+    }
+
+    #[test]
+    fn checks_body_and_time() {
+        assert!(evaluate("body contains \"Ann\"").passed);
+        assert!(!evaluate("body contains Bob").passed);
+        assert!(evaluate("time < 200ms").passed);
+        assert!(!evaluate("time < 100").passed);
+    }
+}
@@ -0,0 +1,549 @@
+use http_request_tool::collection::{Collection, CollectionItem, COLLECTION_FILE};
+use http_request_tool::curl;
+use http_request_tool::executor::Executor;
+use http_request_tool::postman::{self, Import};
+use http_request_tool::report;
+use http_request_tool::request::RequestSpec;
+use http_request_tool::runner::{self, RequestResult, RunReport, Undefined};
+use http_request_tool::variables::{Environment, Variables};
+use http_request_tool::workflow::{self, WorkflowGraph};
+use reqwest::StatusCode;
+use serde_json::json;
+use std::error::Error;
+use std::fs;
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+
+const LAUNCH_USAGE: &str = "usage: http_request_tool [METHOD] [URL | --item <path>] \
+[-H <header>]... [-d <body>] [--env <name>] [--collection <file>] [--workspace <dir>] \
+[--send] [--no-tui [--status | -i | --json]]";
+
+// What to print when sending a request without the TUI
+#[derive(Clone, Copy, PartialEq)]
+pub enum Output {
+    Body,
+    Status,
+    Include,
+    Json,
+}
+
+// What to open the TUI with, or to send without it. The request is a URL or
+// a collection item, with any headers and body given added on.
+pub struct LaunchArgs {
+    pub method: Option<String>,
+    pub url: Option<String>,
+    pub item: Option<String>,
+    pub headers: Vec<(String, String)>,
+    pub body: Option<String>,
+    pub environment: Option<String>,
+    pub collection: Option<PathBuf>,
+    pub workspace: Option<PathBuf>,
+    pub send: bool,
+    pub no_tui: bool,
+    pub output: Output,
+}
+
+pub fn parse_launch_args(args: &[String]) -> Result<LaunchArgs, String> {
+    let mut words = vec![];
+    let mut item = None;
+    let mut headers = vec![];
+    let mut body = None;
+    let mut environment = None;
+    let mut collection = None;
+    let mut workspace = None;
+    let mut send = false;
+    let mut no_tui = false;
+    let mut output = Output::Body;
+
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+        let mut value = || {
+            args.next()
+                .cloned()
+                .ok_or_else(|| format!("{} needs a value\n{}", arg, LAUNCH_USAGE))
+        };
+        match arg.as_str() {
+            "-H" | "--header" => {
+                let header = value()?;
+                let (name, value) = header.split_once(':').unwrap_or((&header, ""));
+                headers.push((name.trim().to_string(), value.trim().to_string()));
+            }
+            "-d" | "--data" => body = Some(curl::read_data(value()?)?),
+            "--item" => item = Some(value()?),
+            "--env" => environment = Some(value()?),
+            "--collection" => collection = Some(PathBuf::from(value()?)),
+            "--workspace" => workspace = Some(PathBuf::from(value()?)),
+            "--send" => send = true,
+            "--no-tui" => no_tui = true,
+            "--status" => output = Output::Status,
+            "-i" | "--include" => output = Output::Include,
+            "--json" => output = Output::Json,
+            flag if flag.starts_with('-') => {
+                return Err(format!("unknown option {}\n{}", flag, LAUNCH_USAGE))
+            }
+            word if words.len() < 2 => words.push(word.to_string()),
+            extra => return Err(format!("unexpected argument {}\n{}", extra, LAUNCH_USAGE)),
+        }
+    }
+
+    // `METHOD URL`, `URL`, or `METHOD` for a collection item
+    let (method, url) = match (words.as_slice(), &item) {
+        ([method, url], None) => (Some(method.to_ascii_uppercase()), Some(url.clone())),
+        ([url], None) => (None, Some(url.clone())),
+        ([method], Some(_)) => (Some(method.to_ascii_uppercase()), None),
+        ([], _) => (None, None),
+        _ => {
+            return Err(format!(
+                "a URL and --item can't be used together\n{}",
+                LAUNCH_USAGE
+            ))
+        }
+    };
+    if url.is_none() && item.is_none() && (!headers.is_empty() || body.is_some() || send) {
+        return Err(format!(
+            "-H, -d and --send need a URL or --item\n{}",
+            LAUNCH_USAGE
+        ));
+    }
+    if output != Output::Body && !no_tui {
+        return Err(format!(
+            "--status, -i and --json need --no-tui\n{}",
+            LAUNCH_USAGE
+        ));
+    }
+
+    Ok(LaunchArgs {
+        method,
+        url,
+        item,
+        headers,
+        body,
+        environment,
+        collection,
+        workspace,
+        send,
+        no_tui,
+        output,
+    })
+}
+
+impl LaunchArgs {
+    // The request asked for, if any, along with the variables of the
+    // collection item it comes from
+    pub fn request(
+        &self,
+        collection: Option<&Collection>,
+    ) -> Result<Option<CollectionItem>, Box<dyn Error>> {
+        let mut item = match (&self.url, &self.item) {
+            (Some(url), _) => CollectionItem {
+                path: String::new(),
+                request: RequestSpec {
+                    name: String::new(),
+                    method: "GET".to_string(),
+                    url: url.clone(),
+                    headers: vec![],
+                    query: vec![],
+                    body: String::new(),
+                    assertions: vec![],
+                    extract: vec![],
+                },
+                variables: Variables::new(),
+            },
+            (None, Some(path)) => collection
+                .ok_or_else(|| format!("--item {} needs a collection", path))?
+                .item(path)?,
+            (None, None) => return Ok(None),
+        };
+
+        let request = &mut item.request;
+        request.headers.extend(self.headers.iter().cloned());
+        if let Some(body) = &self.body {
+            request.body = body.clone();
+            // Like curl, a body given for a plain URL makes it a POST
+            if self.url.is_some() {
+                request.method = "POST".to_string();
+            }
+        }
+        if let Some(method) = &self.method {
+            request.method = method.clone();
+        }
+        Ok(Some(item))
+    }
+}
+
+// Sends the request without the TUI and prints the response to stdout.
+// Returns the exit code: 0 for 1xx to 3xx, 4 for 4xx, and 5 for 5xx.
+pub fn send(args: &LaunchArgs) -> Result<i32, Box<dyn Error>> {
+    let collection = match (&args.collection, &args.item) {
+        (Some(path), _) => Some(Collection::load(path)?),
+        (None, Some(_)) => Some(Collection::load(Path::new(COLLECTION_FILE))?),
+        (None, None) => None,
+    };
+    let item = args
+        .request(collection.as_ref())?
+        .ok_or_else(|| format!("nothing to send: give a URL or --item\n{}", LAUNCH_USAGE))?;
+
+    let environment = match &args.environment {
+        Some(name) => Some(Environment::find(name)?),
+        None => None,
+    };
+    let mut executor = Executor::new(environment.as_ref());
+    let undefined = item.request.undefined(&executor.variables(&item.variables));
+    if !undefined.is_empty() {
+        eprintln!(
+            "warning: undefined variables sent as written: {}",
+            undefined.join(", ")
+        );
+    }
+    let response = executor.execute(&item)?.response;
+
+    let status_line = format!(
+        "HTTP {} {}",
+        response.status,
+        StatusCode::from_u16(response.status)
+            .ok()
+            .and_then(|status| status.canonical_reason())
+            .unwrap_or_default()
+    );
+    let mut stdout = io::stdout().lock();
+    match args.output {
+        Output::Body => {}
+        Output::Status => writeln!(stdout, "{}", status_line.trim_end())?,
+        Output::Include => {
+            writeln!(stdout, "{}", status_line.trim_end())?;
+            for (name, value) in &response.headers {
+                writeln!(stdout, "{}: {}", name, value)?;
+            }
+            writeln!(stdout)?;
+        }
+        Output::Json => {
+            let envelope = json!({
+                "status": response.status,
+                "headers": headers_json(&response.headers),
+                "body": response.body,
+                "time_ms": response.timings.total().as_millis() as u64,
+            });
+            writeln!(stdout, "{}", envelope)?;
+        }
+    }
+    if args.output != Output::Json {
+        stdout.write_all(response.body.as_bytes())?;
+    }
+    stdout.flush()?;
+
+    Ok(match response.status {
+        400..=499 => 4,
+        500..=599 => 5,
+        _ => 0,
+    })
+}
+
+// Headers as an object by name, with the values of a repeated header in an
+// array
+fn headers_json(headers: &[(String, String)]) -> serde_json::Value {
+    let mut object = serde_json::Map::new();
+    for (name, value) in headers {
+        match object.get_mut(name) {
+            Some(serde_json::Value::Array(values)) => values.push(json!(value)),
+            Some(first) => *first = json!([first.take(), value]),
+            None => {
+                object.insert(name.clone(), json!(value));
+            }
+        }
+    }
+    serde_json::Value::Object(object)
+}
+
+const RUN_USAGE: &str = "usage: http_request_tool run <collection.json> \
+[--folder <path> | --workflow <name>] [--env <name>] [--strict] [--junit <file>] [--json <file>]";
+
+struct RunArgs {
+    collection: PathBuf,
+    folder: Option<String>,
+    workflow: Option<String>,
+    environment: Option<String>,
+    junit: Option<PathBuf>,
+    json: Option<PathBuf>,
+    // Fail requests with undefined variables rather than send them
+    strict: bool,
+}
+
+fn parse_run_args(args: &[String]) -> Result<RunArgs, String> {
+    let mut collection = None;
+    let mut folder = None;
+    let mut workflow = None;
+    let mut environment = None;
+    let mut junit = None;
+    let mut json = None;
+    let mut strict = false;
+
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+        let mut value = || {
+            args.next()
+                .cloned()
+                .ok_or_else(|| format!("{} needs a value\n{}", arg, RUN_USAGE))
+        };
+        match arg.as_str() {
+            "--folder" => folder = Some(value()?),
+            "--workflow" => workflow = Some(value()?),
+            "--env" => environment = Some(value()?),
+            "--junit" => junit = Some(PathBuf::from(value()?)),
+            "--json" => json = Some(PathBuf::from(value()?)),
+            "--strict" => strict = true,
+            flag if flag.starts_with('-') => {
+                return Err(format!("unknown option {}\n{}", flag, RUN_USAGE))
+            }
+            path if collection.is_none() => collection = Some(PathBuf::from(path)),
+            extra => return Err(format!("unexpected argument {}\n{}", extra, RUN_USAGE)),
+        }
+    }
+
+    if folder.is_some() && workflow.is_some() {
+        return Err(format!(
+            "--folder and --workflow can't be used together\n{}",
+            RUN_USAGE
+        ));
+    }
+
+    Ok(RunArgs {
+        collection: collection.ok_or_else(|| RUN_USAGE.to_string())?,
+        folder,
+        workflow,
+        environment,
+        junit,
+        json,
+        strict,
+    })
+}
+
+// `run` subcommand: returns whether every request passed
+pub fn run(args: &[String]) -> Result<bool, Box<dyn Error>> {
+    let args = parse_run_args(args)?;
+    let collection = Collection::load(&args.collection)?;
+    let environment = match &args.environment {
+        Some(name) => Some(Environment::find(name)?),
+        None => None,
+    };
+
+    let undefined = if args.strict {
+        Undefined::Fail
+    } else {
+        Undefined::Warn
+    };
+
+    let report = match &args.workflow {
+        Some(name) => {
+            let graph = WorkflowGraph::build(&collection, name)?;
+            let variables = environment
+                .as_ref()
+                .map(|environment| environment.variables.clone())
+                .unwrap_or_default();
+            let run = workflow::execute(
+                &graph,
+                variables,
+                Variables::new(),
+                undefined,
+                |_, _, result| {
+                    if let Some(result) = result {
+                        print_result(result);
+                    }
+                },
+            );
+            RunReport {
+                collection: format!("{} ({})", collection.name, name),
+                environment: environment.map(|environment| environment.name),
+                duration_ms: run.duration_ms,
+                results: run.results,
+            }
+        }
+        None => runner::run(
+            &collection,
+            args.folder.as_deref(),
+            environment.as_ref(),
+            undefined,
+            print_result,
+        )?,
+    };
+
+    let failures = report.failures();
+    let skipped = report
+        .results
+        .iter()
+        .filter(|result| result.skipped)
+        .count();
+    println!(
+        "\n{} requests, {} passed, {} failed, {} skipped ({}ms)",
+        report.results.len(),
+        report.results.len() - failures - skipped,
+        failures,
+        skipped,
+        report.duration_ms
+    );
+
+    if let Some(path) = &args.junit {
+        report::write_junit(&report, path)?;
+        println!("JUnit report written to {}", path.display());
+    }
+    if let Some(path) = &args.json {
+        report::write_json(&report, path)?;
+        println!("JSON report written to {}", path.display());
+    }
+
+    Ok(failures == 0)
+}
+
+fn print_result(result: &RequestResult) {
+    if result.skipped {
+        println!(
+            "- {} {} {} (skipped)",
+            result.path, result.method, result.url
+        );
+        return;
+    }
+    let mark = if result.passed() { "✓" } else { "✗" };
+    let status = match result.status {
+        Some(status) => status.to_string(),
+        None => "---".to_string(),
+    };
+    println!(
+        "{} {} {} {} ({}, {}ms)",
+        mark, result.path, result.method, result.url, status, result.duration_ms
+    );
+
+    if let Some(error) = &result.error {
+        println!("    error: {}", error);
+    }
+    for warning in &result.warnings {
+        println!("    warning: {}", warning);
+    }
+    for assertion in result.assertions.iter().filter(|a| !a.passed) {
+        println!("    ✗ {} ({})", assertion.description, assertion.message);
+    }
+}
+
+const IMPORT_USAGE: &str =
+    "usage: http_request_tool import <postman export.json> [--out <file>] [--force]";
+
+// `import` subcommand: converts a Postman collection or environment export
+pub fn import(args: &[String]) -> Result<(), Box<dyn Error>> {
+    let mut export = None;
+    let mut out = None;
+    let mut force = false;
+
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--out" => {
+                out =
+                    Some(PathBuf::from(args.next().ok_or_else(|| {
+                        format!("--out needs a value\n{}", IMPORT_USAGE)
+                    })?))
+            }
+            "--force" => force = true,
+            flag if flag.starts_with('-') => {
+                return Err(format!("unknown option {}\n{}", flag, IMPORT_USAGE).into())
+            }
+            path if export.is_none() => export = Some(PathBuf::from(path)),
+            extra => return Err(format!("unexpected argument {}\n{}", extra, IMPORT_USAGE).into()),
+        }
+    }
+    let export = export.ok_or(IMPORT_USAGE)?;
+
+    let text = fs::read_to_string(&export)
+        .map_err(|e| format!("could not read {}: {}", export.display(), e))?;
+    let (import, summary) = postman::import(&text)?;
+    // Collections go in the current directory, environments where `--env`
+    // finds them
+    let (what, default_out, json) = match &import {
+        Import::Collection(collection) => (
+            format!("collection \"{}\"", collection.name),
+            PathBuf::from(COLLECTION_FILE),
+            serde_json::to_string_pretty(collection)?,
+        ),
+        Import::Environment(environment) => (
+            format!("environment \"{}\"", environment.name),
+            Path::new("environments").join(format!(
+                "{}.json",
+                environment.name.replace(['/', '\\'], "_")
+            )),
+            serde_json::to_string_pretty(environment)?,
+        ),
+    };
+    let out = out.unwrap_or(default_out);
+    if out.exists() && !force {
+        return Err(format!(
+            "{} already exists, use --force to overwrite it",
+            out.display()
+        )
+        .into());
+    }
+    if let Some(parent) = out.parent().filter(|parent| !parent.as_os_str().is_empty()) {
+        fs::create_dir_all(parent)?;
+    }
+    fs::write(&out, json + "\n")?;
+
+    println!("Imported {} into {}", what, out.display());
+    match import {
+        Import::Collection(_) => println!(
+            "{}, {}, {}",
+            plural(summary.requests, "request"),
+            plural(summary.folders, "folder"),
+            plural(summary.variables, "variable")
+        ),
+        Import::Environment(_) => println!("{}", plural(summary.variables, "variable")),
+    }
+    if !summary.skipped.is_empty() {
+        println!("\nNot imported:");
+        for (location, what) in &summary.skipped {
+            println!("  {}: {}", location, what);
+        }
+    }
+    Ok(())
+}
+
+fn plural(count: usize, noun: &str) -> String {
+    format!("{} {}{}", count, noun, if count == 1 { "" } else { "s" })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn launch(args: &str) -> Result<LaunchArgs, String> {
+        let args: Vec<String> = args.split_whitespace().map(String::from).collect();
+        parse_launch_args(&args)
+    }
+
+    #[test]
+    fn output_options_need_no_tui() {
+        for option in &["--status", "-i", "--include", "--json"] {
+            let error = launch(&format!("https://example.com {}", option))
+                .err()
+                .unwrap();
+            assert!(error.starts_with("--status, -i and --json need --no-tui"));
+            let args = launch(&format!("https://example.com --no-tui {}", option)).unwrap();
+            assert!(args.output != Output::Body);
+        }
+    }
+
+    #[test]
+    fn repeated_headers_become_arrays() {
+        let headers: Vec<(String, String)> = [
+            ("content-type", "application/json"),
+            ("set-cookie", "a=1"),
+            ("set-cookie", "b=2"),
+            ("set-cookie", "c=3"),
+        ]
+        .iter()
+        .map(|(name, value)| (name.to_string(), value.to_string()))
+        .collect();
+        assert_eq!(
+            headers_json(&headers),
+            json!({
+                "content-type": "application/json",
+                "set-cookie": ["a=1", "b=2", "c=3"],
+            })
+        );
+    }
+}
@@ -0,0 +1,66 @@
+use base64::{engine::general_purpose::STANDARD, Engine};
+use std::env;
+use std::error::Error;
+use std::io::{self, Write};
+use std::process::{Command, Stdio};
+
+// Clipboard programs to try, and the environment variable that has to be set
+// for each to be worth running
+const TOOLS: [(&str, &[&str], Option<&str>); 5] = [
+    ("wl-copy", &[], Some("WAYLAND_DISPLAY")),
+    ("xclip", &["-selection", "clipboard"], Some("DISPLAY")),
+    ("xsel", &["--clipboard", "--input"], Some("DISPLAY")),
+    ("pbcopy", &[], None),
+    ("clip.exe", &[], None),
+];
+
+// Copies text to the system clipboard, returning how it was done. Over SSH,
+// or when no clipboard program works, the terminal is asked to do it with
+// an OSC 52 escape sequence.
+pub fn copy(text: &str) -> Result<&'static str, Box<dyn Error>> {
+    let remote = env::var_os("SSH_CONNECTION").is_some() || env::var_os("SSH_TTY").is_some();
+    if !remote {
+        for (program, args, needs) in TOOLS.iter() {
+            if needs.is_some_and(|var| env::var_os(var).is_none()) {
+                continue;
+            }
+            if run(program, args, text).is_ok() {
+                return Ok(program);
+            }
+        }
+    }
+
+    osc52(text)?;
+    Ok("OSC 52")
+}
+
+fn run(program: &str, args: &[&str], text: &str) -> Result<(), Box<dyn Error>> {
+    let mut child = Command::new(program)
+        .args(args)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .spawn()?;
+    child
+        .stdin
+        .take()
+        .ok_or("no stdin")?
+        .write_all(text.as_bytes())?;
+    if child.wait()?.success() {
+        Ok(())
+    } else {
+        Err(format!("{} failed", program).into())
+    }
+}
+
+fn osc52(text: &str) -> io::Result<()> {
+    let mut sequence = format!("\x1b]52;c;{}\x07", STANDARD.encode(text));
+    // tmux only passes the sequence on to the terminal when it's wrapped in
+    // a passthrough, with every escape doubled
+    if env::var_os("TMUX").is_some() {
+        sequence = format!("\x1bPtmux;{}\x1b\\", sequence.replace('\x1b', "\x1b\x1b"));
+    }
+    let mut stdout = io::stdout();
+    stdout.write_all(sequence.as_bytes())?;
+    stdout.flush()
+}
@@ -0,0 +1,113 @@
+use crate::request::RequestSpec;
+use crate::variables::Variables;
+use crate::workflow::Workflow;
+use serde::{Deserialize, Serialize};
+use std::error::Error;
+use std::fs;
+use std::path::Path;
+
+// Where the TUI and `--item` look for a collection by default
+pub const COLLECTION_FILE: &str = "collection.json";
+
+#[derive(Serialize, Deserialize, Default, Clone)]
+pub struct Collection {
+    pub name: String,
+    #[serde(default)]
+    pub variables: Variables,
+    #[serde(default)]
+    pub requests: Vec<RequestSpec>,
+    #[serde(default)]
+    pub folders: Vec<Folder>,
+    #[serde(default)]
+    pub workflows: Vec<Workflow>,
+}
+
+#[derive(Serialize, Deserialize, Default, Clone)]
+pub struct Folder {
+    pub name: String,
+    #[serde(default)]
+    pub variables: Variables,
+    #[serde(default)]
+    pub requests: Vec<RequestSpec>,
+    #[serde(default)]
+    pub folders: Vec<Folder>,
+}
+
+// A request together with where it lives in the collection
+#[derive(Clone)]
+pub struct CollectionItem {
+    pub path: String,
+    pub request: RequestSpec,
+    pub variables: Variables,
+}
+
+impl Collection {
+    pub fn load(path: &Path) -> Result<Collection, Box<dyn Error>> {
+        let text = fs::read_to_string(path)
+            .map_err(|e| format!("could not read {}: {}", path.display(), e))?;
+        Ok(serde_json::from_str(&text)
+            .map_err(|e| format!("invalid collection {}: {}", path.display(), e))?)
+    }
+
+    // The request at `path`, e.g. `users/create`
+    pub fn item(&self, path: &str) -> Result<CollectionItem, String> {
+        self.items(None)?
+            .into_iter()
+            .find(|item| item.path == path)
+            .ok_or_else(|| format!("no request {} in the collection", path))
+    }
+
+    // Every request in the collection, or in the folder at `folder` (written
+    // as `parent/child`), in the order they appear. Folder variables override
+    // those of the folders and collection containing them.
+    pub fn items(&self, folder: Option<&str>) -> Result<Vec<CollectionItem>, String> {
+        let mut variables = self.variables.clone();
+        let mut prefix = String::new();
+        let mut requests = &self.requests;
+        let mut folders = &self.folders;
+
+        if let Some(folder_path) = folder {
+            for name in folder_path.split('/').filter(|name| !name.is_empty()) {
+                let found = folders
+                    .iter()
+                    .find(|f| f.name == name)
+                    .ok_or_else(|| format!("no folder named {} in {}", folder_path, self.name))?;
+                variables.extend(found.variables.clone());
+                prefix = format!("{}{}/", prefix, found.name);
+                requests = &found.requests;
+                folders = &found.folders;
+            }
+        }
+
+        let mut items = vec![];
+        collect_items(requests, folders, &prefix, &variables, &mut items);
+        Ok(items)
+    }
+}
+
+fn collect_items(
+    requests: &[RequestSpec],
+    folders: &[Folder],
+    prefix: &str,
+    variables: &Variables,
+    items: &mut Vec<CollectionItem>,
+) {
+    for request in requests {
+        items.push(CollectionItem {
+            path: format!("{}{}", prefix, request.name),
+            request: request.clone(),
+            variables: variables.clone(),
+        });
+    }
+    for folder in folders {
+        let mut folder_variables = variables.clone();
+        folder_variables.extend(folder.variables.clone());
+        collect_items(
+            &folder.requests,
+            &folder.folders,
+            &format!("{}{}/", prefix, folder.name),
+            &folder_variables,
+            items,
+        );
+    }
+}
@@ -0,0 +1,58 @@
+use crate::layout::Thresholds;
+use serde::Deserialize;
+use std::collections::BTreeMap;
+use std::env;
+use std::error::Error;
+use std::fs;
+use std::path::PathBuf;
+
+// Settings from `config.json` in the config directory
+#[derive(Deserialize, Default)]
+pub struct Config {
+    // The name of the theme to start with
+    #[serde(default)]
+    pub theme: Option<String>,
+    // User-defined themes: a `base` theme to start from and the colours to
+    // change, by name
+    #[serde(default)]
+    pub themes: BTreeMap<String, BTreeMap<String, String>>,
+    // When the layout changes for small terminals
+    #[serde(default)]
+    pub responsive: Thresholds,
+    // Estimate DNS, connect and TLS times for requests sent from the TUI, on
+    // a second connection made before each request
+    #[serde(default, rename = "connection-timings")]
+    pub connection_timings: bool,
+}
+
+impl Config {
+    pub fn load() -> Result<Config, Box<dyn Error>> {
+        let path = match config_dir() {
+            Some(dir) if dir.join("config.json").is_file() => dir.join("config.json"),
+            _ => return Ok(Config::default()),
+        };
+        let text = fs::read_to_string(&path)?;
+        Ok(serde_json::from_str(&text)
+            .map_err(|e| format!("invalid config {}: {}", path.display(), e))?)
+    }
+}
+
+// Whether this is the first run, going by a file left in the config
+// directory the first time
+pub fn first_run() -> bool {
+    let marker = match config_dir() {
+        Some(dir) => dir.join("welcomed"),
+        None => return false,
+    };
+    !marker.exists()
+        && fs::create_dir_all(marker.parent().unwrap()).is_ok()
+        && fs::write(marker, "").is_ok()
+}
+
+// `$XDG_CONFIG_HOME/http-request-tool`, or `~/.config/http-request-tool`
+pub fn config_dir() -> Option<PathBuf> {
+    let config = env::var_os("XDG_CONFIG_HOME")
+        .map(PathBuf::from)
+        .or_else(|| env::var_os("HOME").map(|home| PathBuf::from(home).join(".config")))?;
+    Some(config.join("http-request-tool"))
+}
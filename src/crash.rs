@@ -0,0 +1,76 @@
+use crate::config;
+use crossterm::{
+    cursor::Show,
+    event::{DisableBracketedPaste, DisableMouseCapture},
+    execute,
+    terminal::{disable_raw_mode, LeaveAlternateScreen},
+};
+use std::backtrace::Backtrace;
+use std::fs::{self, OpenOptions};
+use std::io::{self, Write};
+use std::panic;
+use std::path::PathBuf;
+use std::thread;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+// Puts the terminal back the way the shell expects it. It's safe to do this
+// more than once.
+pub fn restore_terminal() {
+    let _ = disable_raw_mode();
+    let _ = execute!(
+        io::stdout(),
+        LeaveAlternateScreen,
+        DisableBracketedPaste,
+        DisableMouseCapture,
+        Show
+    );
+}
+
+// Restores the terminal when it goes out of scope, however the TUI ends
+pub struct TerminalGuard;
+
+impl Drop for TerminalGuard {
+    fn drop(&mut self) {
+        restore_terminal();
+    }
+}
+
+// Appends the message and a backtrace of any panic to the crash log. A panic
+// on the main thread ends the TUI, so the terminal is restored and the
+// message shown. Other threads, like the ones sending requests, only log it:
+// the TUI is still running, and printing would scribble over it.
+pub fn install_panic_hook() {
+    panic::set_hook(Box::new(|info| {
+        let thread = thread::current();
+        let main = thread.name() == Some("main");
+        if main {
+            restore_terminal();
+        }
+        let backtrace = Backtrace::force_capture();
+        let time = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map_or(0, |time| time.as_secs());
+        let report = format!(
+            "time {} (unix), thread {}\n{}\n{}\n",
+            time,
+            thread.name().unwrap_or("unnamed"),
+            info,
+            backtrace
+        );
+        match write_log(&report) {
+            Ok(path) if main => eprintln!("{}\nDetails were written to {}", info, path.display()),
+            Err(_) if main => eprintln!("{}", report),
+            _ => {}
+        }
+    }));
+}
+
+fn write_log(report: &str) -> io::Result<PathBuf> {
+    let dir = config::config_dir()
+        .ok_or_else(|| io::Error::new(io::ErrorKind::NotFound, "no config directory"))?;
+    fs::create_dir_all(&dir)?;
+    let path = dir.join("crash.log");
+    let mut log = OpenOptions::new().create(true).append(true).open(&path)?;
+    log.write_all(report.as_bytes())?;
+    Ok(path)
+}
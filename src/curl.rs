@@ -0,0 +1,412 @@
+use crate::request::RequestSpec;
+use base64::{engine::general_purpose::STANDARD, Engine};
+use std::fs;
+
+// Short options that take a value, which can be attached (`-XPOST`) or the
+// next word
+const SHORT_VALUE_OPTIONS: &str = "XHdubAeFmoxwErTKy";
+
+// Long options whose values are skipped, so they aren't taken for the URL
+const IGNORED_VALUE_OPTIONS: [&str; 20] = [
+    "--connect-timeout",
+    "--max-time",
+    "--output",
+    "--retry",
+    "--retry-delay",
+    "--write-out",
+    "--proxy",
+    "--cacert",
+    "--capath",
+    "--cert",
+    "--key",
+    "--resolve",
+    "--limit-rate",
+    "--range",
+    "--config",
+    "--interface",
+    "--max-redirs",
+    "--dump-header",
+    "--cookie-jar",
+    "--trace",
+];
+
+// Turns a curl command line, like the ones browsers copy, into a request.
+// Options that don't change the request (`-s`, `-L`, `--compressed`, ...)
+// are ignored.
+pub fn parse(command: &str) -> Result<RequestSpec, String> {
+    let mut words = split_words(command)?.into_iter().peekable();
+    if words.peek().map(String::as_str) == Some("curl") {
+        words.next();
+    }
+
+    let mut method = None;
+    let mut url = None;
+    let mut headers: Vec<(String, String)> = vec![];
+    let mut data: Vec<String> = vec![];
+    let mut json = false;
+    let mut get = false;
+
+    while let Some(word) = words.next() {
+        // Split `-XPOST` and `-sSL` style words into an option and its value
+        let (option, attached) = match word.strip_prefix('-') {
+            Some(rest) if !rest.starts_with('-') && rest.len() > 1 => {
+                match rest.find(|c| SHORT_VALUE_OPTIONS.contains(c)) {
+                    Some(i) => {
+                        let (_, value) = rest.split_at(i + 1);
+                        let value = Some(value.to_string()).filter(|value| !value.is_empty());
+                        (format!("-{}", &rest[i..i + 1]), value)
+                    }
+                    None => continue,
+                }
+            }
+            _ => (word.clone(), None),
+        };
+        let mut value = || {
+            attached
+                .clone()
+                .or_else(|| words.next())
+                .ok_or_else(|| format!("{} needs a value", option))
+        };
+
+        match option.as_str() {
+            "-X" | "--request" => method = Some(value()?.to_ascii_uppercase()),
+            "-H" | "--header" => {
+                let header = value()?;
+                let (name, value) = header.split_once(':').unwrap_or((&header, ""));
+                headers.push((name.trim().to_string(), value.trim().to_string()));
+            }
+            "-d" | "--data" | "--data-ascii" | "--data-binary" => data.push(read_data(value()?)?),
+            "--data-raw" => data.push(value()?),
+            "--data-urlencode" => data.push(encode_data(&value()?)?),
+            "--json" => {
+                data.push(read_data(value()?)?);
+                json = true;
+            }
+            "-G" | "--get" => get = true,
+            "-I" | "--head" => method = Some("HEAD".to_string()),
+            "-u" | "--user" => {
+                let credentials = STANDARD.encode(value()?);
+                headers.push((
+                    "Authorization".to_string(),
+                    format!("Basic {}", credentials),
+                ));
+            }
+            "--oauth2-bearer" => {
+                headers.push(("Authorization".to_string(), format!("Bearer {}", value()?)))
+            }
+            "-A" | "--user-agent" => headers.push(("User-Agent".to_string(), value()?)),
+            "-e" | "--referer" => headers.push(("Referer".to_string(), value()?)),
+            "-b" | "--cookie" => {
+                // Without an `=` it's the name of a cookie file
+                let cookie = value()?;
+                if cookie.contains('=') {
+                    headers.push(("Cookie".to_string(), cookie));
+                }
+            }
+            "-F" | "--form" => return Err("multipart forms (-F) aren't supported".to_string()),
+            "--url" => url = Some(value()?),
+            "-m" | "-o" | "-x" | "-w" | "-E" | "-r" | "-T" | "-K" | "-y" => {
+                value()?;
+            }
+            option if IGNORED_VALUE_OPTIONS.contains(&option) => {
+                value()?;
+            }
+            option if option.starts_with('-') => {}
+            _ if url.is_none() => url = Some(word),
+            _ => return Err(format!("unexpected argument: {}", word)),
+        }
+    }
+
+    let mut url = url.ok_or("no URL in the curl command")?;
+    let mut body = data.join("&");
+    if get && !body.is_empty() {
+        let separator = if url.contains('?') { '&' } else { '?' };
+        url = format!("{}{}{}", url, separator, body);
+        body.clear();
+    }
+    if json {
+        for name in ["Content-Type", "Accept"] {
+            if !headers.iter().any(|(h, _)| h.eq_ignore_ascii_case(name)) {
+                headers.push((name.to_string(), "application/json".to_string()));
+            }
+        }
+    }
+    let method = method.unwrap_or_else(|| if body.is_empty() { "GET" } else { "POST" }.to_string());
+
+    Ok(RequestSpec {
+        name: String::new(),
+        method,
+        url,
+        headers,
+        query: vec![],
+        body,
+        assertions: vec![],
+        extract: vec![],
+    })
+}
+
+// `@file` reads the data from a file, like curl does
+pub fn read_data(data: String) -> Result<String, String> {
+    match data.strip_prefix('@') {
+        Some(path) => fs::read_to_string(path)
+            .map(|text| text.trim_end_matches(['\r', '\n']).to_string())
+            .map_err(|e| format!("could not read {}: {}", path, e)),
+        None => Ok(data),
+    }
+}
+
+// `--data-urlencode` takes `content`, `=content`, `name=content`, `@file` or
+// `name@file`, and encodes the content (but not the name) like curl does
+fn encode_data(data: &str) -> Result<String, String> {
+    let (name, content) = match data.find(['=', '@']) {
+        Some(i) if data[i..].starts_with('@') => {
+            let path = &data[i + 1..];
+            let content =
+                fs::read_to_string(path).map_err(|e| format!("could not read {}: {}", path, e))?;
+            (&data[..i], content)
+        }
+        Some(i) => (&data[..i], data[i + 1..].to_string()),
+        None => ("", data.to_string()),
+    };
+    if name.is_empty() {
+        Ok(percent_encode(&content))
+    } else {
+        Ok(format!("{}={}", name, percent_encode(&content)))
+    }
+}
+
+// Everything but unreserved characters becomes `%XX`
+fn percent_encode(text: &str) -> String {
+    let mut encoded = String::new();
+    for byte in text.bytes() {
+        match byte {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'.' | b'_' | b'~' => {
+                encoded.push(byte as char)
+            }
+            _ => encoded.push_str(&format!("%{:02X}", byte)),
+        }
+    }
+    encoded
+}
+
+// Splits a command into words the way a POSIX shell would, handling quotes,
+// backslashes, line continuations and bash's `$'...'` strings
+fn split_words(command: &str) -> Result<Vec<String>, String> {
+    let mut words = vec![];
+    let mut word: Option<String> = None;
+    let mut chars = command.chars().peekable();
+
+    while let Some(c) = chars.next() {
+        match c {
+            ' ' | '\t' | '\r' | '\n' => {
+                if let Some(word) = word.take() {
+                    words.push(word);
+                }
+            }
+            '\\' => match chars.next() {
+                Some('\n') => {}
+                Some('\r') if chars.peek() == Some(&'\n') => {
+                    chars.next();
+                }
+                Some(c) => word.get_or_insert_with(String::new).push(c),
+                None => {}
+            },
+            '\'' => {
+                let word = word.get_or_insert_with(String::new);
+                loop {
+                    match chars.next() {
+                        Some('\'') => break,
+                        Some(c) => word.push(c),
+                        None => return Err("unterminated ' quote".to_string()),
+                    }
+                }
+            }
+            '"' => {
+                let word = word.get_or_insert_with(String::new);
+                loop {
+                    match chars.next() {
+                        Some('"') => break,
+                        Some('\\') => match chars.next() {
+                            Some(c @ ('"' | '\\' | '$' | '`')) => word.push(c),
+                            Some('\n') => {}
+                            Some(c) => {
+                                word.push('\\');
+                                word.push(c);
+                            }
+                            None => return Err("unterminated \" quote".to_string()),
+                        },
+                        Some(c) => word.push(c),
+                        None => return Err("unterminated \" quote".to_string()),
+                    }
+                }
+            }
+            '$' if chars.peek() == Some(&'\'') => {
+                chars.next();
+                let word = word.get_or_insert_with(String::new);
+                loop {
+                    match chars.next() {
+                        Some('\'') => break,
+                        Some('\\') => match chars.next() {
+                            Some('n') => word.push('\n'),
+                            Some('t') => word.push('\t'),
+                            Some('r') => word.push('\r'),
+                            Some(c) => word.push(c),
+                            None => return Err("unterminated $' quote".to_string()),
+                        },
+                        Some(c) => word.push(c),
+                        None => return Err("unterminated $' quote".to_string()),
+                    }
+                }
+            }
+            c => word.get_or_insert_with(String::new).push(c),
+        }
+    }
+    if let Some(word) = word {
+        words.push(word);
+    }
+    Ok(words)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn split(command: &str) -> Vec<String> {
+        split_words(command).unwrap()
+    }
+
+    #[test]
+    fn splits_on_whitespace() {
+        assert_eq!(
+            split("  curl\t-s \n https://a.test  "),
+            ["curl", "-s", "https://a.test"]
+        );
+        assert!(split("   ").is_empty());
+    }
+
+    #[test]
+    fn splits_quoted_words() {
+        assert_eq!(split(r#"'a b'"c d"e"#), ["a bc de"]);
+        assert_eq!(split(r"'it'\''s'"), ["it's"]);
+        assert_eq!(split(r#""say \"hi\" \n""#), [r#"say "hi" \n"#]);
+        assert_eq!(split("''"), [""]);
+        assert_eq!(split(r"$'a\tb\n\'c'"), ["a\tb\n'c"]);
+    }
+
+    #[test]
+    fn splits_backslashes_and_continuations() {
+        assert_eq!(split("a\\ b c"), ["a b", "c"]);
+        assert_eq!(split("curl \\\n  -s \\\r\n  url"), ["curl", "-s", "url"]);
+    }
+
+    #[test]
+    fn rejects_unterminated_quotes() {
+        assert!(split_words("curl 'url").is_err());
+        assert!(split_words("curl \"url").is_err());
+        assert!(split_words("curl $'url").is_err());
+    }
+
+    #[test]
+    fn parses_a_get() {
+        let request = parse("curl -sSL --compressed 'https://a.test/users?page=2'").unwrap();
+        assert_eq!(request.method, "GET");
+        assert_eq!(request.url, "https://a.test/users?page=2");
+        assert!(request.headers.is_empty());
+        assert!(request.body.is_empty());
+    }
+
+    #[test]
+    fn parses_a_post_with_headers_and_data() {
+        let request = parse(
+            "curl https://a.test/login -H 'Content-Type: application/json' \
+             -H'X-Id:7' --data-raw '{\"user\":\"ann\"}'",
+        )
+        .unwrap();
+        assert_eq!(request.method, "POST");
+        assert_eq!(
+            request.headers,
+            [
+                ("Content-Type".to_string(), "application/json".to_string()),
+                ("X-Id".to_string(), "7".to_string()),
+            ]
+        );
+        assert_eq!(request.body, r#"{"user":"ann"}"#);
+    }
+
+    #[test]
+    fn parses_attached_and_combined_options() {
+        let request = parse("curl -sXput -d a=1 -d b=2 https://a.test").unwrap();
+        assert_eq!(request.method, "PUT");
+        assert_eq!(request.body, "a=1&b=2");
+    }
+
+    #[test]
+    fn encodes_urlencoded_data() {
+        let path = std::env::temp_dir().join(format!("curl-test-{}.txt", std::process::id()));
+        fs::write(&path, "a&b\n").unwrap();
+        let command = format!(
+            "curl https://a.test --data-urlencode 'q=a b&c' --data-urlencode =x/y \
+             --data-urlencode 'ünï' --data-urlencode note@{0} --data-urlencode @{0}",
+            path.display()
+        );
+        let request = parse(&command);
+        fs::remove_file(&path).unwrap();
+
+        let request = request.unwrap();
+        assert_eq!(request.method, "POST");
+        assert_eq!(
+            request.body,
+            "q=a%20b%26c&x%2Fy&%C3%BCn%C3%AF&note=a%26b%0A&a%26b%0A"
+        );
+        assert!(parse("curl https://a.test --data-urlencode @/missing/file").is_err());
+    }
+
+    #[test]
+    fn moves_data_into_the_url_with_get() {
+        let request = parse("curl -G -d q=x https://a.test/search?lang=en").unwrap();
+        assert_eq!(request.method, "GET");
+        assert_eq!(request.url, "https://a.test/search?lang=en&q=x");
+        assert!(request.body.is_empty());
+    }
+
+    #[test]
+    fn parses_auth_and_json_options() {
+        let request = parse("curl -u ann:secret --json '{}' --url https://a.test").unwrap();
+        assert_eq!(request.url, "https://a.test");
+        assert_eq!(
+            request.headers,
+            [
+                (
+                    "Authorization".to_string(),
+                    "Basic YW5uOnNlY3JldA==".to_string()
+                ),
+                ("Content-Type".to_string(), "application/json".to_string()),
+                ("Accept".to_string(), "application/json".to_string()),
+            ]
+        );
+        let request = parse("curl --oauth2-bearer t0k -I https://a.test").unwrap();
+        assert_eq!(request.method, "HEAD");
+        assert_eq!(request.headers[0].1, "Bearer t0k");
+    }
+
+    #[test]
+    fn skips_the_values_of_ignored_options() {
+        let request = parse("curl -o out.json --max-time 5 -b jar.txt https://a.test").unwrap();
+        assert_eq!(request.url, "https://a.test");
+        assert!(request.headers.is_empty());
+    }
+
+    #[test]
+    fn rejects_bad_commands() {
+        assert_eq!(
+            parse("curl -s").err().unwrap(),
+            "no URL in the curl command"
+        );
+        assert_eq!(
+            parse("curl https://a.test -H").err().unwrap(),
+            "-H needs a value"
+        );
+        assert!(parse("curl https://a.test https://b.test").is_err());
+        assert!(parse("curl -F a=@f https://a.test").is_err());
+    }
+}
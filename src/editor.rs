@@ -0,0 +1,83 @@
+use std::env;
+use std::error::Error;
+use std::fs::{self, File, OpenOptions};
+use std::io::{self, Write};
+#[cfg(unix)]
+use std::os::unix::fs::OpenOptionsExt;
+use std::path::PathBuf;
+use std::process::{self, Command};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+// Edits `text` in the user's editor, using a temp file with the given
+// extension so the editor picks the right syntax. The terminal must already
+// be out of raw mode.
+pub fn edit(text: &str, extension: &str) -> Result<String, Box<dyn Error>> {
+    let editor = ["VISUAL", "EDITOR"]
+        .iter()
+        .filter_map(|name| env::var(name).ok())
+        .find(|editor| !editor.trim().is_empty())
+        .unwrap_or_else(|| "vi".to_string());
+    // Allow editors configured with arguments, e.g. `code --wait`
+    let mut words = editor.split_whitespace();
+    let program = words.next().unwrap_or_default();
+
+    let (temp_file, mut file) = create_temp_file(extension)?;
+    file.write_all(text.as_bytes())?;
+    drop(file);
+
+    let status = Command::new(program).args(words).arg(&temp_file.0).status();
+    let edited = fs::read_to_string(&temp_file.0);
+    drop(temp_file);
+
+    let status = status.map_err(|e| format!("could not run {}: {}", program, e))?;
+    if !status.success() {
+        return Err(format!("{} exited with {}", program, status).into());
+    }
+    let mut edited = edited?;
+
+    // Most editors add a newline at the end of the file
+    if edited.ends_with('\n') && !text.ends_with('\n') {
+        edited.pop();
+        if edited.ends_with('\r') {
+            edited.pop();
+        }
+    }
+    Ok(edited)
+}
+
+// Removed however editing ends
+struct TempFile(PathBuf);
+
+impl Drop for TempFile {
+    fn drop(&mut self) {
+        let _ = fs::remove_file(&self.0);
+    }
+}
+
+// Request bodies often hold tokens, so the file is only readable by the
+// user. It has a name nobody can guess ahead of time, and is never opened if
+// it already exists, e.g. as a symlink planted by someone else.
+fn create_temp_file(extension: &str) -> io::Result<(TempFile, File)> {
+    let mut attempt = 0;
+    loop {
+        let nanos = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map_or(0, |time| time.subsec_nanos());
+        let path = env::temp_dir().join(format!(
+            "http-request-tool-{}-{:08x}{}.{}",
+            process::id(),
+            nanos,
+            attempt,
+            extension
+        ));
+        let mut options = OpenOptions::new();
+        options.write(true).create_new(true);
+        #[cfg(unix)]
+        options.mode(0o600);
+        match options.open(&path) {
+            Ok(file) => return Ok((TempFile(path), file)),
+            Err(e) if e.kind() == io::ErrorKind::AlreadyExists && attempt < 100 => attempt += 1,
+            Err(e) => return Err(e),
+        }
+    }
+}
@@ -0,0 +1,89 @@
+use crate::assertion::AssertionResult;
+use crate::collection::CollectionItem;
+use crate::extraction;
+use crate::request::{RequestSpec, ResponseRecord};
+use crate::variables::{Environment, Variables};
+use std::error::Error;
+
+// A request as it was sent, with its response and the results of its tests
+// and captures
+pub struct Exchange {
+    pub request: RequestSpec,
+    pub response: ResponseRecord,
+    pub results: Vec<AssertionResult>,
+}
+
+impl Exchange {
+    pub fn passed(&self) -> bool {
+        self.results.iter().all(|result| result.passed)
+    }
+}
+
+// Sends requests one after another, the way the TUI and `run` do. Values
+// captured from a response are kept for the requests after it.
+#[derive(Clone, Default)]
+pub struct Executor {
+    pub environment: Variables,
+    pub runtime: Variables,
+}
+
+impl Executor {
+    pub fn new(environment: Option<&Environment>) -> Executor {
+        Executor {
+            environment: environment
+                .map(|environment| environment.variables.clone())
+                .unwrap_or_default(),
+            runtime: Variables::new(),
+        }
+    }
+
+    // Sends a request from a collection with its variables filled in
+    pub fn execute(&mut self, item: &CollectionItem) -> Result<Exchange, Box<dyn Error>> {
+        let request = item.request.resolve(&self.variables(&item.variables));
+        self.send(request)
+    }
+
+    // Sends a request that already has its variables filled in
+    pub fn send(&mut self, request: RequestSpec) -> Result<Exchange, Box<dyn Error>> {
+        send(request, &mut self.environment, &mut self.runtime)
+    }
+
+    pub fn variables(&self, own: &Variables) -> Variables {
+        variables(own, &self.environment, &self.runtime)
+    }
+}
+
+// The variables a request sees. The environment overrides the request's
+// own (from its collection and folders), and captured values override both.
+pub fn variables(own: &Variables, environment: &Variables, runtime: &Variables) -> Variables {
+    let mut variables = own.clone();
+    variables.extend(environment.clone());
+    variables.extend(runtime.clone());
+    variables
+}
+
+// Sends a request, then checks its assertions and stores what it captures in
+// `environment` or `runtime`
+pub fn send(
+    request: RequestSpec,
+    environment: &mut Variables,
+    runtime: &mut Variables,
+) -> Result<Exchange, Box<dyn Error>> {
+    let response = request.send()?;
+    let results = request
+        .assertions
+        .iter()
+        .map(|assertion| assertion.evaluate(&response))
+        .chain(extraction::apply(
+            &request.extract,
+            &response,
+            environment,
+            runtime,
+        ))
+        .collect();
+    Ok(Exchange {
+        request,
+        response,
+        results,
+    })
+}
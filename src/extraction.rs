@@ -0,0 +1,293 @@
+use crate::assertion::AssertionResult;
+use crate::json_path;
+use crate::request::ResponseRecord;
+use crate::variables::Variables;
+use regex::Regex;
+use serde::{de, Deserialize, Deserializer, Serialize, Serializer};
+use serde_json::Value;
+use std::fmt;
+use std::str::FromStr;
+
+// Extraction rules are written one per line, for example:
+//
+//   token = json $.access_token
+//   location = header Location
+//   session = cookie sid
+//   id = regex "id":\s*(\d+)
+//   env token = json $.access_token
+//
+// Values go into runtime variables unless the line starts with `env`, in
+// which case they're stored in the active environment.
+#[derive(Clone)]
+pub struct Extraction {
+    pub variable: String,
+    pub scope: Scope,
+    pub source: Source,
+}
+
+#[derive(Clone, Copy, PartialEq)]
+pub enum Scope {
+    Runtime,
+    Environment,
+}
+
+#[derive(Clone)]
+pub enum Source {
+    Json(String),
+    Header(String),
+    Cookie(String),
+    Regex(Regex),
+}
+
+impl Extraction {
+    pub fn extract(&self, response: &ResponseRecord) -> Result<String, String> {
+        match &self.source {
+            Source::Json(path) => {
+                let body: Value = serde_json::from_str(&response.body)
+                    .map_err(|e| format!("body is not JSON: {}", e))?;
+                match json_path::select(&body, path)? {
+                    Some(Value::String(s)) => Ok(s.clone()),
+                    Some(value) => Ok(value.to_string()),
+                    None => Err(format!("{} not found", path)),
+                }
+            }
+            Source::Header(name) => response
+                .header(name)
+                .map(str::to_string)
+                .ok_or_else(|| format!("header {} is missing", name)),
+            Source::Cookie(name) => response
+                .headers
+                .iter()
+                .rev()
+                .filter(|(header, _)| header.eq_ignore_ascii_case("Set-Cookie"))
+                .filter_map(|(_, value)| {
+                    let pair = value.split(';').next()?;
+                    let mut parts = pair.splitn(2, '=');
+                    let cookie = parts.next()?.trim();
+                    let value = parts.next()?.trim();
+                    if cookie == name {
+                        Some(value.to_string())
+                    } else {
+                        None
+                    }
+                })
+                .next()
+                .ok_or_else(|| format!("cookie {} was not set", name)),
+            // The first capture group if there is one, otherwise the whole match
+            Source::Regex(regex) => regex
+                .captures(&response.body)
+                .and_then(|captures| captures.get(1).or_else(|| captures.get(0)))
+                .map(|m| m.as_str().to_string())
+                .ok_or_else(|| format!("{} did not match", regex)),
+        }
+    }
+}
+
+// Runs every rule against the response, storing what it finds. The results
+// are shown alongside the assertions.
+pub fn apply(
+    extractions: &[Extraction],
+    response: &ResponseRecord,
+    environment: &mut Variables,
+    runtime: &mut Variables,
+) -> Vec<AssertionResult> {
+    extractions
+        .iter()
+        .map(|extraction| {
+            let (passed, message) = match extraction.extract(response) {
+                Ok(value) => {
+                    let message = format!("{} = {}", extraction.variable, value);
+                    let variables = match extraction.scope {
+                        Scope::Runtime => &mut *runtime,
+                        Scope::Environment => &mut *environment,
+                    };
+                    variables.insert(extraction.variable.clone(), value);
+                    (true, message)
+                }
+                Err(message) => (false, message),
+            };
+            AssertionResult {
+                description: extraction.to_string(),
+                passed,
+                message,
+            }
+        })
+        .collect()
+}
+
+impl fmt::Display for Extraction {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if self.scope == Scope::Environment {
+            write!(f, "env ")?;
+        }
+        write!(f, "{} = ", self.variable)?;
+        match &self.source {
+            Source::Json(path) => write!(f, "json {}", path),
+            Source::Header(name) => write!(f, "header {}", name),
+            Source::Cookie(name) => write!(f, "cookie {}", name),
+            Source::Regex(regex) => write!(f, "regex {}", regex),
+        }
+    }
+}
+
+impl FromStr for Extraction {
+    type Err = String;
+
+    fn from_str(line: &str) -> Result<Extraction, String> {
+        let invalid = || format!("invalid extraction: {}", line);
+        let (target, rest) = line.split_once('=').ok_or_else(invalid)?;
+
+        let target: Vec<&str> = target.split_whitespace().collect();
+        let (scope, variable) = match target.as_slice() {
+            [variable] => (Scope::Runtime, variable),
+            ["env", variable] => (Scope::Environment, variable),
+            _ => return Err(invalid()),
+        };
+
+        let rest = rest.trim();
+        let (kind, argument) = match rest.find(char::is_whitespace) {
+            Some(i) => (&rest[..i], rest[i..].trim()),
+            None => return Err(invalid()),
+        };
+        let source = match kind {
+            "json" => Source::Json(argument.to_string()),
+            "header" => Source::Header(argument.to_string()),
+            "cookie" => Source::Cookie(argument.to_string()),
+            "regex" => Source::Regex(Regex::new(argument).map_err(|e| e.to_string())?),
+            _ => return Err(invalid()),
+        };
+
+        Ok(Extraction {
+            variable: variable.to_string(),
+            scope,
+            source,
+        })
+    }
+}
+
+impl Serialize for Extraction {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(self)
+    }
+}
+
+impl<'de> Deserialize<'de> for Extraction {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Extraction, D::Error> {
+        String::deserialize(deserializer)?
+            .parse()
+            .map_err(de::Error::custom)
+    }
+}
+
+pub fn parse_lines(text: &str) -> Vec<Result<Extraction, String>> {
+    text.lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty() && !line.starts_with('#'))
+        .map(str::parse)
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::timing::Timings;
+
+    fn response() -> ResponseRecord {
+        ResponseRecord {
+            status: 200,
+            headers: vec![
+                ("Location".to_string(), "/users/42".to_string()),
+                ("Set-Cookie".to_string(), "sid=old; Path=/".to_string()),
+                ("set-cookie".to_string(), "sid=abc; HttpOnly".to_string()),
+            ],
+            body: r#"{"access_token":"t0k","user":{"id":42}}"#.to_string(),
+            timings: Timings::default(),
+        }
+    }
+
+    fn extract(line: &str) -> Result<String, String> {
+        line.parse::<Extraction>().unwrap().extract(&response())
+    }
+
+    #[test]
+    fn parses_lines() {
+        let parsed = parse_lines("token = json $.access_token\n# skipped\nenv id = regex (\\d+)");
+        assert_eq!(parsed.len(), 2);
+        let token = parsed[0].as_ref().unwrap();
+        assert_eq!(token.variable, "token");
+        assert!(token.scope == Scope::Runtime);
+        let id = parsed[1].as_ref().unwrap();
+        assert_eq!(id.variable, "id");
+        assert!(id.scope == Scope::Environment);
+    }
+
+    #[test]
+    fn rejects_invalid_extractions() {
+        for line in [
+            "token json $.token",
+            "token = json",
+            "token = body $.token",
+            "one two = json $.token",
+            "id = regex (",
+        ] {
+            assert!(line.parse::<Extraction>().is_err(), "{}", line);
+        }
+    }
+
+    #[test]
+    fn displays_as_written() {
+        for line in [
+            "token = json $.access_token",
+            "env location = header Location",
+            "session = cookie sid",
+        ] {
+            assert_eq!(line.parse::<Extraction>().unwrap().to_string(), line);
+        }
+    }
+
+    #[test]
+    fn extracts_from_each_source() {
+        assert_eq!(extract("t = json $.access_token").unwrap(), "t0k");
+        assert_eq!(extract("t = json $.user.id").unwrap(), "42");
+        assert_eq!(extract("t = json $.user").unwrap(), r#"{"id":42}"#);
+        assert_eq!(extract("t = header location").unwrap(), "/users/42");
+        assert_eq!(extract("t = cookie sid").unwrap(), "abc");
+        assert_eq!(extract("t = regex \"id\":(\\d+)").unwrap(), "42");
+        assert_eq!(extract("t = regex t0k").unwrap(), "t0k");
+    }
+
+    #[test]
+    fn reports_missing_values() {
+        assert_eq!(
+            extract("t = json $.refresh").unwrap_err(),
+            "$.refresh not found"
+        );
+        assert_eq!(
+            extract("t = header ETag").unwrap_err(),
+            "header ETag is missing"
+        );
+        assert_eq!(
+            extract("t = cookie theme").unwrap_err(),
+            "cookie theme was not set"
+        );
+    }
+
+    #[test]
+    fn applies_into_the_right_scope() {
+        let extractions: Vec<Extraction> =
+            parse_lines("token = json $.access_token\nenv id = json $.user.id\nx = header ETag")
+                .into_iter()
+                .collect::<Result<_, _>>()
+                .unwrap();
+        let mut environment = Variables::new();
+        let mut runtime = Variables::new();
+        let results = apply(&extractions, &response(), &mut environment, &mut runtime);
+
+        assert_eq!(runtime.get("token").unwrap(), "t0k");
+        assert_eq!(environment.get("id").unwrap(), "42");
+        assert!(!runtime.contains_key("x"));
+        let passed: Vec<bool> = results.iter().map(|result| result.passed).collect();
+        assert_eq!(passed, [true, true, false]);
+        assert_eq!(results[0].message, "token = t0k");
+    }
+}
@@ -0,0 +1,18 @@
+use http_request_tool::request::{RequestSpec, ResponseRecord};
+use serde::{Deserialize, Serialize};
+
+#[derive(Clone, Serialize, Deserialize)]
+pub struct HistoryEntry {
+    pub request: RequestSpec,
+    pub response: ResponseRecord,
+}
+
+// Responses kept per tab. Only the last one is shown or saved, so a long
+// session doesn't need more.
+const HISTORY_LIMIT: usize = 20;
+
+pub fn record(history: &mut Vec<HistoryEntry>, entry: HistoryEntry) {
+    history.push(entry);
+    let excess = history.len().saturating_sub(HISTORY_LIMIT);
+    history.drain(..excess);
+}
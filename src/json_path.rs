@@ -0,0 +1,122 @@
+use serde_json::Value;
+
+enum Segment {
+    Key(String),
+    Index(usize),
+}
+
+// Supports the subset of JSONPath needed to point at a single value:
+// `$`, `.key`, `[0]`, `['key']` and `["key"]`
+pub fn select<'a>(value: &'a Value, path: &str) -> Result<Option<&'a Value>, String> {
+    let mut current = value;
+    for segment in parse(path)? {
+        let next = match segment {
+            Segment::Key(key) => current.get(key.as_str()),
+            Segment::Index(index) => current.get(index),
+        };
+        match next {
+            Some(next) => current = next,
+            None => return Ok(None),
+        }
+    }
+    Ok(Some(current))
+}
+
+fn parse(path: &str) -> Result<Vec<Segment>, String> {
+    let rest = path
+        .strip_prefix('$')
+        .ok_or_else(|| format!("JSON path must start with '$': {}", path))?;
+    let chars: Vec<char> = rest.chars().collect();
+    let mut segments = vec![];
+    let mut i = 0;
+
+    while i < chars.len() {
+        match chars[i] {
+            '.' => {
+                let start = i + 1;
+                i = start;
+                while i < chars.len() && chars[i] != '.' && chars[i] != '[' {
+                    i += 1;
+                }
+                if i == start {
+                    return Err(format!("empty key in JSON path: {}", path));
+                }
+                segments.push(Segment::Key(chars[start..i].iter().collect()));
+            }
+            '[' => {
+                // A quoted key can contain `]`, so look for the closing
+                // bracket after the closing quote
+                let quote = chars
+                    .get(i + 1)
+                    .copied()
+                    .filter(|c| *c == '\'' || *c == '"');
+                let search_from = match quote {
+                    Some(quote) => chars[i + 2..]
+                        .iter()
+                        .position(|c| *c == quote)
+                        .map_or(chars.len(), |offset| i + 2 + offset),
+                    None => i,
+                };
+                let close = chars[search_from..]
+                    .iter()
+                    .position(|c| *c == ']')
+                    .map(|offset| search_from + offset)
+                    .ok_or_else(|| format!("unclosed '[' in JSON path: {}", path))?;
+                let inner: String = chars[i + 1..close].iter().collect();
+                let quoted = quote.is_some_and(|quote| inner.len() >= 2 && inner.ends_with(quote));
+                if quoted {
+                    segments.push(Segment::Key(inner[1..inner.len() - 1].to_string()));
+                } else {
+                    let index = inner
+                        .parse()
+                        .map_err(|_| format!("invalid index '{}' in JSON path: {}", inner, path))?;
+                    segments.push(Segment::Index(index));
+                }
+                i = close + 1;
+            }
+            c => return Err(format!("unexpected '{}' in JSON path: {}", c, path)),
+        }
+    }
+
+    Ok(segments)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn selects_values() {
+        let value = json!({"user": {"name": "Ann", "tags": ["a", "b"]}, "odd key": 1});
+        assert_eq!(select(&value, "$").unwrap(), Some(&value));
+        assert_eq!(select(&value, "$.user.name").unwrap(), Some(&json!("Ann")));
+        assert_eq!(select(&value, "$.user.tags[1]").unwrap(), Some(&json!("b")));
+        assert_eq!(select(&value, "$['odd key']").unwrap(), Some(&json!(1)));
+        assert_eq!(
+            select(&json!({"a]b": 2}), "$['a]b']").unwrap(),
+            Some(&json!(2))
+        );
+        assert_eq!(
+            select(&value, "$[\"user\"].tags").unwrap(),
+            Some(&json!(["a", "b"]))
+        );
+    }
+
+    #[test]
+    fn missing_values_are_none() {
+        let value = json!({"items": [1, 2]});
+        assert_eq!(select(&value, "$.other").unwrap(), None);
+        assert_eq!(select(&value, "$.items[2]").unwrap(), None);
+        assert_eq!(select(&value, "$.items.length").unwrap(), None);
+        assert_eq!(select(&value, "$[0]").unwrap(), None);
+    }
+
+    #[test]
+    fn rejects_invalid_paths() {
+        let value = json!({});
+        for path in ["user", "$..user", "$.user[", "$[x]", "$[-1]"] {
+            assert!(select(&value, path).is_err(), "{}", path);
+        }
+    }
+}
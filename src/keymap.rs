@@ -0,0 +1,710 @@
+use crate::action::Action;
+use crate::config;
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+use std::collections::BTreeMap;
+use std::error::Error;
+use std::fmt;
+use std::fs;
+use std::str::FromStr;
+
+// A key with its modifiers. Shift is left out for characters, since it's
+// already in the character (`G` rather than `shift+g`).
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct Key {
+    pub code: KeyCode,
+    pub modifiers: KeyModifiers,
+}
+
+impl From<KeyEvent> for Key {
+    fn from(event: KeyEvent) -> Key {
+        let mut modifiers =
+            event.modifiers & (KeyModifiers::CONTROL | KeyModifiers::ALT | KeyModifiers::SHIFT);
+        if let KeyCode::Char(_) | KeyCode::BackTab = event.code {
+            modifiers.remove(KeyModifiers::SHIFT);
+        }
+        Key {
+            code: event.code,
+            modifiers,
+        }
+    }
+}
+
+const KEY_NAMES: [(KeyCode, &str); 16] = [
+    (KeyCode::Esc, "esc"),
+    (KeyCode::Enter, "enter"),
+    (KeyCode::Tab, "tab"),
+    (KeyCode::BackTab, "backtab"),
+    (KeyCode::Backspace, "backspace"),
+    (KeyCode::Delete, "delete"),
+    (KeyCode::Insert, "insert"),
+    (KeyCode::Home, "home"),
+    (KeyCode::End, "end"),
+    (KeyCode::PageUp, "pageup"),
+    (KeyCode::PageDown, "pagedown"),
+    (KeyCode::Up, "up"),
+    (KeyCode::Down, "down"),
+    (KeyCode::Left, "left"),
+    (KeyCode::Right, "right"),
+    (KeyCode::Char(' '), "space"),
+];
+
+const MODIFIER_NAMES: [(KeyModifiers, &str); 3] = [
+    (KeyModifiers::CONTROL, "ctrl"),
+    (KeyModifiers::ALT, "alt"),
+    (KeyModifiers::SHIFT, "shift"),
+];
+
+impl fmt::Display for Key {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for (modifier, name) in MODIFIER_NAMES.iter() {
+            if self.modifiers.contains(*modifier) {
+                write!(f, "{}+", name)?;
+            }
+        }
+        match KEY_NAMES.iter().find(|(code, _)| *code == self.code) {
+            Some((_, name)) => write!(f, "{}", name),
+            None => match self.code {
+                KeyCode::Char(c) => write!(f, "{}", c),
+                KeyCode::F(n) => write!(f, "f{}", n),
+                _ => write!(f, "?"),
+            },
+        }
+    }
+}
+
+// Keys are written like `q`, `G`, `f1`, `pageup`, `ctrl+e` or
+// `ctrl+shift+left`
+impl FromStr for Key {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Key, String> {
+        let (modifier_names, name) = match s.strip_suffix("++") {
+            Some(rest) => (rest, "+"),
+            None if s == "+" => ("", "+"),
+            None => match s.rsplit_once('+') {
+                Some((modifiers, name)) => (modifiers, name),
+                None => ("", s),
+            },
+        };
+
+        let mut modifiers = KeyModifiers::NONE;
+        for modifier in modifier_names.split('+').filter(|m| !m.is_empty()) {
+            modifiers |= MODIFIER_NAMES
+                .iter()
+                .find(|(_, name)| name.eq_ignore_ascii_case(modifier))
+                .map(|(modifier, _)| *modifier)
+                .ok_or_else(|| format!("unknown modifier {} in {}", modifier, s))?;
+        }
+
+        let mut chars = name.chars();
+        let code = match (chars.next(), chars.next()) {
+            (Some(c), None) => KeyCode::Char(c),
+            _ => {
+                let lower = name.to_ascii_lowercase();
+                match KEY_NAMES.iter().find(|(_, key)| *key == lower) {
+                    Some((code, _)) => *code,
+                    None => lower
+                        .strip_prefix('f')
+                        .and_then(|n| n.parse().ok())
+                        .map(KeyCode::F)
+                        .ok_or_else(|| format!("unknown key: {}", s))?,
+                }
+            }
+        };
+        if let KeyCode::Char(_) | KeyCode::BackTab = code {
+            modifiers.remove(KeyModifiers::SHIFT);
+        }
+        Ok(Key { code, modifiers })
+    }
+}
+
+type Sequence = Vec<Key>;
+
+fn parse_sequence(s: &str) -> Result<Sequence, String> {
+    let keys: Sequence = s
+        .split_whitespace()
+        .map(str::parse)
+        .collect::<Result<_, _>>()?;
+    if keys.is_empty() {
+        return Err("empty key sequence".to_string());
+    }
+    Ok(keys)
+}
+
+fn sequence_string(keys: &[Key]) -> String {
+    keys.iter()
+        .map(Key::to_string)
+        .collect::<Vec<String>>()
+        .join(" ")
+}
+
+// The editor and endpoint share the `text` bindings, and every context falls
+// back to the `global` ones
+const CONTEXTS: [&str; 13] = [
+    "global",
+    "navigation",
+    "tabs",
+    "text",
+    "editor",
+    "endpoint",
+    "request-tabs",
+    "method",
+    "response-tabs",
+    "response",
+    "workflows",
+    "palette",
+    "help",
+];
+
+fn chain(context: &str) -> Vec<&str> {
+    match context {
+        "editor" | "endpoint" => vec![context, "text", "global"],
+        "global" => vec!["global"],
+        _ => vec![context, "global"],
+    }
+}
+
+// The default keys for each action, by context
+type Defaults = &'static [(Action, &'static [&'static str])];
+
+const DEFAULTS: &[(&str, Defaults)] = &[
+    (
+        "global",
+        &[(Action::Help, &["f1"]), (Action::Palette, &["ctrl+p"])],
+    ),
+    (
+        "navigation",
+        &[
+            (Action::Quit, &["q"]),
+            (Action::Enter, &["enter"]),
+            (Action::Left, &["h", "left"]),
+            (Action::Right, &["l", "right"]),
+            (Action::Up, &["k", "up"]),
+            (Action::Down, &["j", "down"]),
+            (Action::OpenWorkflows, &["w"]),
+            (Action::CopyBody, &["b"]),
+            (Action::CopyHeaders, &["H"]),
+            (Action::CopyUrl, &["u"]),
+            (Action::CopyCurl, &["C"]),
+            (Action::Help, &["?"]),
+            (Action::Palette, &[":"]),
+            (Action::GrowRequest, &[">"]),
+            (Action::ShrinkRequest, &["<"]),
+            (Action::ToggleStacked, &["|"]),
+            (Action::ToggleZoom, &["z"]),
+        ],
+    ),
+    (
+        "tabs",
+        &[
+            (Action::Quit, &["q"]),
+            (Action::Exit, &["esc"]),
+            (Action::Left, &["h", "left"]),
+            (Action::Right, &["l", "right"]),
+            (Action::DeleteTab, &["backspace", "delete"]),
+        ],
+    ),
+    (
+        "text",
+        &[
+            (Action::Left, &["left"]),
+            (Action::Right, &["right"]),
+            (Action::Up, &["up"]),
+            (Action::Down, &["down"]),
+            (Action::WordLeft, &["ctrl+left"]),
+            (Action::WordRight, &["ctrl+right"]),
+            (Action::LineStart, &["home"]),
+            (Action::LineEnd, &["end"]),
+            (Action::SelectLeft, &["shift+left"]),
+            (Action::SelectRight, &["shift+right"]),
+            (Action::SelectUp, &["shift+up"]),
+            (Action::SelectDown, &["shift+down"]),
+            (Action::SelectWordLeft, &["ctrl+shift+left"]),
+            (Action::SelectWordRight, &["ctrl+shift+right"]),
+            (Action::SelectLineStart, &["shift+home"]),
+            (Action::SelectLineEnd, &["shift+end"]),
+            (Action::SelectAll, &["ctrl+a"]),
+            (Action::Backspace, &["backspace"]),
+            (Action::Delete, &["delete"]),
+            (Action::Copy, &["ctrl+c"]),
+            (Action::Cut, &["ctrl+x"]),
+            (Action::Paste, &["ctrl+v"]),
+            (Action::Undo, &["ctrl+z"]),
+            (Action::Redo, &["ctrl+y"]),
+            (Action::ToggleLineNumbers, &["ctrl+l"]),
+        ],
+    ),
+    (
+        "editor",
+        &[
+            (Action::Exit, &["esc"]),
+            (Action::Newline, &["enter"]),
+            (Action::Indent, &["tab"]),
+            (Action::ExternalEditor, &["ctrl+e"]),
+            (Action::FormatJson, &["ctrl+f"]),
+            (Action::MinifyJson, &["ctrl+u"]),
+        ],
+    ),
+    ("endpoint", &[(Action::Exit, &["esc", "enter"])]),
+    (
+        "request-tabs",
+        &[
+            (Action::Exit, &["esc", "enter"]),
+            (Action::Left, &["h", "left"]),
+            (Action::Right, &["l", "right"]),
+        ],
+    ),
+    (
+        "method",
+        &[
+            (Action::Exit, &["esc", "enter"]),
+            (Action::Left, &["h", "left"]),
+            (Action::Right, &["l", "right"]),
+        ],
+    ),
+    (
+        "response-tabs",
+        &[
+            (Action::Exit, &["esc", "enter"]),
+            (Action::Left, &["h", "left"]),
+            (Action::Right, &["l", "right"]),
+        ],
+    ),
+    (
+        "response",
+        &[
+            (Action::Quit, &["q"]),
+            (Action::Exit, &["esc"]),
+            (Action::Up, &["k", "up"]),
+            (Action::Down, &["j", "down"]),
+            (Action::PageUp, &["pageup"]),
+            (Action::PageDown, &["pagedown"]),
+            (Action::Top, &["g", "home"]),
+            (Action::Bottom, &["G", "end"]),
+            (Action::CopyNode, &["y"]),
+            (Action::CopyBody, &["b"]),
+            (Action::CopyHeaders, &["H"]),
+            (Action::CopyUrl, &["u"]),
+            (Action::CopyCurl, &["C"]),
+        ],
+    ),
+    (
+        "workflows",
+        &[
+            (Action::Exit, &["esc"]),
+            (Action::Enter, &["enter"]),
+            (Action::Up, &["k", "up"]),
+            (Action::Down, &["j", "down"]),
+        ],
+    ),
+    (
+        "palette",
+        &[
+            (Action::Exit, &["esc"]),
+            (Action::Enter, &["enter"]),
+            (Action::Up, &["up"]),
+            (Action::Down, &["down"]),
+            (Action::Backspace, &["backspace"]),
+        ],
+    ),
+    (
+        "help",
+        &[
+            (Action::Up, &["k", "up"]),
+            (Action::Down, &["j", "down"]),
+            (Action::PageUp, &["pageup"]),
+            (Action::PageDown, &["pagedown"]),
+            (Action::Top, &["g", "home"]),
+            (Action::Bottom, &["G", "end"]),
+        ],
+    ),
+];
+
+pub enum Press {
+    Action(Action),
+    // The key starts a sequence, so wait for the next one
+    Pending,
+    // Nothing is bound to the key, which may be text to type
+    Unbound(Key),
+}
+
+// keymap.json: context -> action -> key sequences
+type Config = BTreeMap<String, BTreeMap<String, Vec<String>>>;
+
+pub struct Keymap {
+    // Context -> action -> key sequences
+    bindings: BTreeMap<String, BTreeMap<Action, Vec<Sequence>>>,
+    pending: Sequence,
+}
+
+impl Default for Keymap {
+    fn default() -> Keymap {
+        let mut bindings = BTreeMap::new();
+        for (context, defaults) in DEFAULTS {
+            let actions: &mut BTreeMap<Action, Vec<Sequence>> =
+                bindings.entry(context.to_string()).or_default();
+            for (action, keys) in defaults.iter() {
+                let sequences = keys
+                    .iter()
+                    .map(|keys| parse_sequence(keys).unwrap())
+                    .collect();
+                actions.insert(*action, sequences);
+            }
+        }
+        Keymap {
+            bindings,
+            pending: vec![],
+        }
+    }
+}
+
+impl Keymap {
+    // The default keymap with the user's keymap.json on top, if there is one
+    pub fn load() -> Result<Keymap, Box<dyn Error>> {
+        let mut keymap = Keymap::default();
+        let path = match config::config_dir() {
+            Some(dir) if dir.join("keymap.json").is_file() => dir.join("keymap.json"),
+            _ => return Ok(keymap),
+        };
+        let text = fs::read_to_string(&path)?;
+        let config: Config = serde_json::from_str(&text)
+            .map_err(|e| format!("invalid keymap {}: {}", path.display(), e))?;
+        keymap.bind(config)?;
+        Ok(keymap)
+    }
+
+    // Binding an action replaces its default keys, so `[]` unbinds it
+    fn bind(&mut self, config: Config) -> Result<(), String> {
+        for (context, actions) in config {
+            if !CONTEXTS.contains(&context.as_str()) {
+                return Err(format!("unknown keymap context: {}", context));
+            }
+            let bindings = self.bindings.entry(context).or_default();
+            for (action, keys) in actions {
+                let sequences = keys
+                    .iter()
+                    .map(|keys| parse_sequence(keys))
+                    .collect::<Result<_, _>>()?;
+                bindings.insert(action.parse()?, sequences);
+            }
+        }
+        self.check()
+    }
+
+    // Within a context, every key sequence has to lead to a single action. It
+    // can't be bound twice, or be the start of a longer sequence. Keys from
+    // the contexts a context falls back on are shadowed instead.
+    fn check(&self) -> Result<(), String> {
+        let mut conflicts = vec![];
+        for context in CONTEXTS.iter() {
+            let bound = self.bindings_in(vec![context]);
+            for (i, (keys, action)) in bound.iter().enumerate() {
+                for (other_keys, other_action) in &bound[i + 1..] {
+                    let overlap = keys.starts_with(other_keys) || other_keys.starts_with(keys);
+                    if overlap && action != other_action {
+                        conflicts.push(format!(
+                            "{}: \"{}\" ({}) conflicts with \"{}\" ({})",
+                            context,
+                            sequence_string(keys),
+                            action,
+                            sequence_string(other_keys),
+                            other_action
+                        ));
+                    }
+                }
+            }
+        }
+        conflicts.dedup();
+        if conflicts.is_empty() {
+            Ok(())
+        } else {
+            Err(format!("keymap conflicts:\n{}", conflicts.join("\n")))
+        }
+    }
+
+    // The bindings that apply in a context, most specific first
+    fn bindings_for<'a>(&'a self, context: &'a str) -> Vec<(&'a Sequence, Action)> {
+        self.bindings_in(chain(context))
+    }
+
+    // A binding shadows any in the later contexts that it overlaps, so a
+    // context can use a key differently from `global`
+    fn bindings_in<'a>(&'a self, contexts: Vec<&'a str>) -> Vec<(&'a Sequence, Action)> {
+        let mut bound: Vec<(&Sequence, Action)> = vec![];
+        for actions in contexts
+            .into_iter()
+            .filter_map(|context| self.bindings.get(context))
+        {
+            let visible: Vec<(&Sequence, Action)> = actions
+                .iter()
+                .flat_map(|(action, sequences)| sequences.iter().map(move |keys| (keys, *action)))
+                .filter(|(keys, _)| {
+                    !bound
+                        .iter()
+                        .any(|(other, _)| keys.starts_with(other) || other.starts_with(keys))
+                })
+                .collect();
+            bound.extend(visible);
+        }
+        bound
+    }
+
+    pub fn press(&mut self, context: &str, event: KeyEvent) -> Press {
+        let key = Key::from(event);
+        let mut keys = std::mem::take(&mut self.pending);
+        keys.push(key);
+
+        let mut longer = false;
+        for (sequence, action) in self.bindings_for(context) {
+            if *sequence == keys {
+                return Press::Action(action);
+            }
+            longer |= sequence.starts_with(&keys);
+        }
+        if longer {
+            self.pending = keys;
+            return Press::Pending;
+        }
+
+        // A sequence that went nowhere is dropped, and its last key is
+        // tried on its own
+        if keys.len() > 1 {
+            return self.press(context, event);
+        }
+        Press::Unbound(key)
+    }
+
+    // The keys for an action in a context, e.g. to show in the palette
+    pub fn keys_for(&self, context: &str, action: Action) -> Option<String> {
+        self.help(context, true)
+            .into_iter()
+            .find(|(_, bound)| *bound == action)
+            .map(|(keys, _)| keys)
+    }
+
+    // The keys for each action in a context, for the help overlay. The
+    // `global` bindings can be left out, to be listed once on their own.
+    pub fn help(&self, context: &str, global: bool) -> Vec<(String, Action)> {
+        let contexts = chain(context)
+            .into_iter()
+            .filter(|c| global || context == "global" || *c != "global")
+            .collect();
+        let mut lines: Vec<(String, Action)> = vec![];
+        for (keys, action) in self.bindings_in(contexts) {
+            let keys = sequence_string(keys);
+            match lines.iter_mut().find(|(_, bound)| *bound == action) {
+                Some((existing, _)) => {
+                    existing.push_str(", ");
+                    existing.push_str(&keys);
+                }
+                None => lines.push((keys, action)),
+            }
+        }
+        lines
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn key(code: KeyCode, modifiers: KeyModifiers) -> Key {
+        Key { code, modifiers }
+    }
+
+    fn bind(json: &str) -> Result<Keymap, String> {
+        let mut keymap = Keymap::default();
+        keymap.bind(serde_json::from_str(json).unwrap())?;
+        Ok(keymap)
+    }
+
+    fn press(keymap: &mut Keymap, context: &str, code: KeyCode) -> Option<Action> {
+        match keymap.press(context, KeyEvent::new(code, KeyModifiers::NONE)) {
+            Press::Action(action) => Some(action),
+            _ => None,
+        }
+    }
+
+    #[test]
+    fn parses_keys() {
+        assert_eq!("q".parse(), Ok(key(KeyCode::Char('q'), KeyModifiers::NONE)));
+        assert_eq!("G".parse(), Ok(key(KeyCode::Char('G'), KeyModifiers::NONE)));
+        assert_eq!(
+            "shift+G".parse(),
+            Ok(key(KeyCode::Char('G'), KeyModifiers::NONE))
+        );
+        assert_eq!("F12".parse(), Ok(key(KeyCode::F(12), KeyModifiers::NONE)));
+        assert_eq!(
+            "PageUp".parse(),
+            Ok(key(KeyCode::PageUp, KeyModifiers::NONE))
+        );
+        assert_eq!("+".parse(), Ok(key(KeyCode::Char('+'), KeyModifiers::NONE)));
+        assert_eq!(
+            "ctrl++".parse(),
+            Ok(key(KeyCode::Char('+'), KeyModifiers::CONTROL))
+        );
+        assert_eq!(
+            "ctrl+shift+left".parse(),
+            Ok(key(
+                KeyCode::Left,
+                KeyModifiers::CONTROL | KeyModifiers::SHIFT
+            ))
+        );
+    }
+
+    #[test]
+    fn rejects_unknown_keys() {
+        assert_eq!(
+            "hyper+a".parse::<Key>(),
+            Err("unknown modifier hyper in hyper+a".to_string())
+        );
+        assert_eq!(
+            "escape".parse::<Key>(),
+            Err("unknown key: escape".to_string())
+        );
+        assert_eq!("fx".parse::<Key>(), Err("unknown key: fx".to_string()));
+        assert!(parse_sequence("  ").is_err());
+    }
+
+    #[test]
+    fn displays_keys_as_written() {
+        for keys in [
+            "q",
+            "ctrl+e",
+            "ctrl+shift+left",
+            "g g",
+            "f5",
+            "space",
+            "alt+enter",
+        ] {
+            assert_eq!(sequence_string(&parse_sequence(keys).unwrap()), keys);
+        }
+    }
+
+    #[test]
+    fn key_events_ignore_shift_on_characters() {
+        let event = KeyEvent::new(KeyCode::Char('G'), KeyModifiers::SHIFT);
+        assert_eq!(Key::from(event), "G".parse().unwrap());
+        let event = KeyEvent::new(KeyCode::Up, KeyModifiers::SHIFT);
+        assert_eq!(Key::from(event), "shift+up".parse().unwrap());
+    }
+
+    #[test]
+    fn the_defaults_do_not_conflict() {
+        assert_eq!(Keymap::default().check(), Ok(()));
+    }
+
+    #[test]
+    fn bindings_replace_the_defaults() {
+        let mut keymap = bind(r#"{"navigation": {"quit": ["Q"], "open-workflows": []}}"#).unwrap();
+        assert_eq!(
+            keymap.keys_for("navigation", Action::Quit),
+            Some("Q".to_string())
+        );
+        assert_eq!(keymap.keys_for("navigation", Action::OpenWorkflows), None);
+        assert_eq!(press(&mut keymap, "navigation", KeyCode::Char('q')), None);
+        assert_eq!(
+            press(&mut keymap, "navigation", KeyCode::Char('Q')),
+            Some(Action::Quit)
+        );
+        // Other contexts keep their defaults
+        assert_eq!(
+            press(&mut keymap, "response", KeyCode::Char('q')),
+            Some(Action::Quit)
+        );
+    }
+
+    #[test]
+    fn sequences_wait_for_their_next_key() {
+        let mut keymap = bind(r#"{"response": {"top": ["g g"]}}"#).unwrap();
+        assert!(matches!(
+            keymap.press(
+                "response",
+                KeyEvent::new(KeyCode::Char('g'), KeyModifiers::NONE)
+            ),
+            Press::Pending
+        ));
+        assert_eq!(
+            press(&mut keymap, "response", KeyCode::Char('g')),
+            Some(Action::Top)
+        );
+        // A sequence that goes nowhere falls back to its last key
+        press(&mut keymap, "response", KeyCode::Char('g'));
+        assert_eq!(
+            press(&mut keymap, "response", KeyCode::Char('j')),
+            Some(Action::Down)
+        );
+    }
+
+    #[test]
+    fn detects_conflicts() {
+        let error = bind(r#"{"navigation": {"copy-url": ["q"]}}"#)
+            .err()
+            .unwrap();
+        assert_eq!(
+            error,
+            "keymap conflicts:\nnavigation: \"q\" (quit) conflicts with \"q\" (copy-url)"
+        );
+        // A binding can't be the start of a longer one
+        assert!(bind(r#"{"response": {"copy-url": ["y y"]}}"#).is_err());
+        assert!(bind(r#"{"global": {"quit": ["ctrl+p"]}}"#).is_err());
+    }
+
+    #[test]
+    fn contexts_shadow_the_keys_they_fall_back_on() {
+        let mut keymap = bind(
+            r#"{
+                "global": {"help": ["esc"], "palette": ["g"]},
+                "text": {"undo": ["f2"]},
+                "editor": {"format-json": ["f2"], "exit": ["ctrl+k ctrl+s"]}
+            }"#,
+        )
+        .unwrap();
+
+        // `esc` closes the palette rather than opening the help
+        assert_eq!(
+            press(&mut keymap, "palette", KeyCode::Esc),
+            Some(Action::Exit)
+        );
+        assert_eq!(
+            press(&mut keymap, "navigation", KeyCode::Esc),
+            Some(Action::Help)
+        );
+        assert_eq!(keymap.keys_for("palette", Action::Help), None);
+        assert_eq!(
+            keymap.keys_for("navigation", Action::Help),
+            Some("?, esc".to_string())
+        );
+        // `g` is `top` in the response, and opens the palette elsewhere
+        assert_eq!(
+            press(&mut keymap, "response", KeyCode::Char('g')),
+            Some(Action::Top)
+        );
+        assert_eq!(
+            press(&mut keymap, "workflows", KeyCode::Char('g')),
+            Some(Action::Palette)
+        );
+        // The editor shadows the keys shared with the endpoint
+        assert_eq!(
+            press(&mut keymap, "editor", KeyCode::F(2)),
+            Some(Action::FormatJson)
+        );
+        assert_eq!(
+            press(&mut keymap, "endpoint", KeyCode::F(2)),
+            Some(Action::Undo)
+        );
+        assert_eq!(keymap.keys_for("editor", Action::Undo), None);
+    }
+
+    #[test]
+    fn rejects_unknown_contexts_and_actions() {
+        assert_eq!(
+            bind(r#"{"sidebar": {"quit": ["q"]}}"#).err().unwrap(),
+            "unknown keymap context: sidebar"
+        );
+        assert_eq!(
+            bind(r#"{"navigation": {"explode": ["x"]}}"#).err().unwrap(),
+            "unknown action: explode"
+        );
+    }
+}
@@ -0,0 +1,324 @@
+use crate::config;
+use serde::{Deserialize, Serialize};
+use std::error::Error;
+use std::fs;
+use tui::layout::{Constraint, Direction, Layout as TuiLayout, Rect};
+
+pub mod pane_identifiers {
+    pub const PANE_TABS: usize = 0;
+    pub const PANE_ENDPOINT: usize = 1;
+    pub const PANE_BODY_HEADER_SELECT: usize = 2;
+    pub const PANE_REQUEST_ENTRY: usize = 3;
+    pub const PANE_METHOD_SELECT: usize = 4;
+    pub const PANE_SEND_BUTTON: usize = 5;
+    pub const PANE_RESPONSE_TABS: usize = 6;
+    pub const PANE_RESPONSE_TEXT: usize = 7;
+}
+use pane_identifiers::*;
+
+pub const PANE_COUNT: usize = 8;
+
+// What each pane is called in the status bar
+pub const PANE_NAMES: [&str; PANE_COUNT] = [
+    "Tabs",
+    "URL",
+    "Request tabs",
+    "Request editor",
+    "Method",
+    "Send",
+    "Response tabs",
+    "Response",
+];
+
+// The split between the request and response can be changed in steps of
+// this many percent, within these limits
+const SPLIT_STEP: u16 = 5;
+const SPLIT_LIMITS: (u16, u16) = (20, 80);
+
+#[derive(Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Side {
+    Request,
+    Response,
+}
+
+// The user's layout choices, saved between runs
+#[derive(Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Layout {
+    // The request above the response, rather than beside it
+    pub stacked: bool,
+    // How much of the space the request gets, in percent
+    pub split: u16,
+    // Only one side is shown
+    pub zoom: Option<Side>,
+    pub hide_tabs: bool,
+    pub hide_timing: bool,
+}
+
+impl Default for Layout {
+    fn default() -> Layout {
+        Layout {
+            stacked: false,
+            split: 50,
+            zoom: None,
+            hide_tabs: false,
+            hide_timing: false,
+        }
+    }
+}
+
+// Terminal sizes below which the layout changes to fit, from the `responsive`
+// section of config.json
+#[derive(Clone, Copy, Deserialize)]
+#[serde(default, rename_all = "kebab-case")]
+pub struct Thresholds {
+    // Narrower than this, the request goes above the response
+    pub stack_width: u16,
+    // Shorter than this, stacked sides are shown one at a time
+    pub single_pane_height: u16,
+    // Smaller than this, nothing fits
+    pub min_width: u16,
+    pub min_height: u16,
+}
+
+impl Default for Thresholds {
+    fn default() -> Thresholds {
+        Thresholds {
+            stack_width: 100,
+            single_pane_height: 30,
+            min_width: 40,
+            min_height: 18,
+        }
+    }
+}
+
+// How the layout has to change to fit the terminal
+#[derive(Clone, Copy, PartialEq)]
+pub enum Fit {
+    Full,
+    Stacked,
+    SinglePane,
+    TooSmall,
+}
+
+impl Thresholds {
+    pub fn fit(&self, layout: &Layout, size: Rect) -> Fit {
+        if size.width < self.min_width || size.height < self.min_height {
+            return Fit::TooSmall;
+        }
+        let stacked = layout.stacked || size.width < self.stack_width;
+        if layout.zoom.is_none() && stacked && size.height < self.single_pane_height {
+            Fit::SinglePane
+        } else if stacked && !layout.stacked {
+            Fit::Stacked
+        } else {
+            Fit::Full
+        }
+    }
+}
+
+// The side of the screen a pane is on. The tabs go with the request.
+pub fn side(pane: usize) -> Side {
+    match pane {
+        PANE_RESPONSE_TABS | PANE_RESPONSE_TEXT => Side::Response,
+        _ => Side::Request,
+    }
+}
+
+// A part of the screen: a pane, the timing chart, the status bar, or a split
+// into parts
+pub enum Region {
+    Pane(usize),
+    Timing,
+    Status,
+    Split(Direction, Vec<(Constraint, Region)>),
+}
+
+// Where everything was put. Hidden panes get an empty area.
+#[derive(Default)]
+pub struct Areas {
+    pub panes: [Rect; PANE_COUNT],
+    pub timing: Rect,
+    pub status: Rect,
+}
+
+impl Layout {
+    // `$XDG_CONFIG_HOME/http-request-tool/layout.json`, if it has been saved
+    pub fn load() -> Result<Layout, Box<dyn Error>> {
+        let path = match config::config_dir() {
+            Some(dir) if dir.join("layout.json").is_file() => dir.join("layout.json"),
+            _ => return Ok(Layout::default()),
+        };
+        let text = fs::read_to_string(&path)?;
+        Ok(serde_json::from_str(&text)
+            .map_err(|e| format!("invalid layout {}: {}", path.display(), e))?)
+    }
+
+    pub fn save(&self) -> Result<(), Box<dyn Error>> {
+        let dir = config::config_dir().ok_or("no config directory")?;
+        fs::create_dir_all(&dir)?;
+        fs::write(dir.join("layout.json"), serde_json::to_string_pretty(self)?)?;
+        Ok(())
+    }
+
+    // The layout changed to fit the terminal. A single pane shows `side`.
+    pub fn fitted(&self, fit: Fit, side: Side) -> Layout {
+        let mut layout = self.clone();
+        match fit {
+            Fit::Stacked => layout.stacked = true,
+            Fit::SinglePane => layout.zoom = Some(side),
+            Fit::Full | Fit::TooSmall => {}
+        }
+        layout
+    }
+
+    pub fn resize(&mut self, steps: i16) {
+        let split = self.split as i16 + steps * SPLIT_STEP as i16;
+        self.split = split.clamp(SPLIT_LIMITS.0 as i16, SPLIT_LIMITS.1 as i16) as u16;
+    }
+
+    // The whole screen, described once for both drawing and moving between
+    // panes. A new pane only needs a place here.
+    pub fn tree(&self, show_timing: bool) -> Region {
+        let request = Region::Split(
+            Direction::Vertical,
+            vec![
+                (Constraint::Length(3), Region::Pane(PANE_ENDPOINT)),
+                (Constraint::Length(3), Region::Pane(PANE_BODY_HEADER_SELECT)),
+                (Constraint::Min(2), Region::Pane(PANE_REQUEST_ENTRY)),
+                (
+                    Constraint::Length(3),
+                    Region::Split(
+                        Direction::Horizontal,
+                        vec![
+                            (Constraint::Min(1), Region::Pane(PANE_METHOD_SELECT)),
+                            (Constraint::Length(6), Region::Pane(PANE_SEND_BUTTON)),
+                        ],
+                    ),
+                ),
+            ],
+        );
+        let timing_height = if show_timing && !self.hide_timing {
+            8
+        } else {
+            0
+        };
+        let response = Region::Split(
+            Direction::Vertical,
+            vec![
+                (Constraint::Length(3), Region::Pane(PANE_RESPONSE_TABS)),
+                (Constraint::Min(1), Region::Pane(PANE_RESPONSE_TEXT)),
+                (Constraint::Length(timing_height), Region::Timing),
+            ],
+        );
+
+        let body = match self.zoom {
+            Some(Side::Request) => request,
+            Some(Side::Response) => response,
+            None => Region::Split(
+                if self.stacked {
+                    Direction::Vertical
+                } else {
+                    Direction::Horizontal
+                },
+                vec![
+                    (Constraint::Percentage(self.split), request),
+                    (Constraint::Percentage(100 - self.split), response),
+                ],
+            ),
+        };
+        let tabs_height = if self.hide_tabs { 0 } else { 3 };
+        Region::Split(
+            Direction::Vertical,
+            vec![
+                (Constraint::Length(tabs_height), Region::Pane(PANE_TABS)),
+                (Constraint::Min(2), body),
+                (Constraint::Length(1), Region::Status),
+            ],
+        )
+    }
+}
+
+impl Region {
+    pub fn areas(&self, area: Rect) -> Areas {
+        let mut areas = Areas::default();
+        self.place(area, &mut areas);
+        areas
+    }
+
+    fn place(&self, area: Rect, areas: &mut Areas) {
+        match self {
+            Region::Pane(pane) => areas.panes[*pane] = area,
+            Region::Timing => areas.timing = area,
+            Region::Status => areas.status = area,
+            Region::Split(direction, parts) => {
+                let constraints: Vec<Constraint> = parts.iter().map(|(c, _)| *c).collect();
+                let rects = TuiLayout::default()
+                    .direction(direction.clone())
+                    .constraints(constraints)
+                    .split(area);
+                for ((_, part), rect) in parts.iter().zip(rects) {
+                    part.place(rect, areas);
+                }
+            }
+        }
+    }
+}
+
+#[derive(Clone, Copy, PartialEq)]
+pub enum Move {
+    Left,
+    Right,
+    Up,
+    Down,
+}
+
+// The pane next to `from` in a direction, going by where the panes are on
+// screen: the nearest one that lines up with it best
+pub fn neighbour(panes: &[Rect; PANE_COUNT], from: usize, direction: Move) -> Option<usize> {
+    let current = panes[from];
+    panes
+        .iter()
+        .enumerate()
+        .filter(|(pane, area)| *pane != from && area.area() > 0)
+        .filter_map(|(pane, area)| {
+            // The gap in the direction of travel, how much the panes line up
+            // across it, and how far apart their starts are across it. Panes
+            // that line up with the start of the current one come first.
+            let (gap, overlap, offset) = match direction {
+                Move::Left | Move::Right => {
+                    let gap = match direction {
+                        Move::Left => current.x.checked_sub(area.right())?,
+                        _ => area.x.checked_sub(current.right())?,
+                    };
+                    let overlap = overlap(current.y, current.bottom(), area.y, area.bottom());
+                    let offset = current.y.abs_diff(area.y);
+                    (gap, overlap, offset)
+                }
+                Move::Up | Move::Down => {
+                    let gap = match direction {
+                        Move::Up => current.y.checked_sub(area.bottom())?,
+                        _ => area.y.checked_sub(current.bottom())?,
+                    };
+                    let overlap = overlap(current.x, current.right(), area.x, area.right());
+                    let offset = current.x.abs_diff(area.x);
+                    (gap, overlap, offset)
+                }
+            };
+            if overlap == 0 {
+                return None;
+            }
+            let aligned = match direction {
+                Move::Left | Move::Right => current.y >= area.y && current.y < area.bottom(),
+                Move::Up | Move::Down => current.x >= area.x && current.x < area.right(),
+            };
+            Some(((gap, !aligned, u16::MAX - overlap, offset, pane), pane))
+        })
+        .min_by_key(|(score, _)| *score)
+        .map(|(_, pane)| pane)
+}
+
+fn overlap(start: u16, end: u16, other_start: u16, other_end: u16) -> u16 {
+    end.min(other_end).saturating_sub(start.max(other_start))
+}
@@ -0,0 +1,17 @@
+// The parts of the tool that don't depend on the TUI: requests and responses,
+// collections, environments and variables, and sending requests with their
+// tests and captures. The binary in main.rs is built on top of this, and the
+// same collections can be driven from Rust, e.g. in integration tests.
+pub mod assertion;
+pub mod collection;
+pub mod curl;
+pub mod executor;
+pub mod extraction;
+pub mod json_path;
+pub mod postman;
+pub mod report;
+pub mod request;
+pub mod runner;
+pub mod timing;
+pub mod variables;
+pub mod workflow;
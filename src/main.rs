@@ -1,30 +1,127 @@
+mod action;
 mod app;
-use crate::app::App;
-mod ui_graph;
+use crate::action::Action;
+use crate::app::{App, InputMode};
+use crate::keymap::Press;
+mod cli;
+mod clipboard;
+mod config;
+mod crash;
+mod editor;
+mod history;
+mod keymap;
+mod layout;
+mod session;
+mod theme;
 
 use crossterm::{
     event,
-    event::KeyCode,
+    event::{
+        DisableBracketedPaste, DisableMouseCapture, EnableBracketedPaste, EnableMouseCapture,
+        KeyEvent, MouseButton, MouseEvent, MouseEventKind,
+    },
     execute,
     terminal::{disable_raw_mode, enable_raw_mode, EnterAlternateScreen, LeaveAlternateScreen},
 };
+use std::env;
 use std::error::Error;
-use std::io;
-use std::sync::mpsc;
+use std::io::{self, IsTerminal};
+use std::panic::{self, AssertUnwindSafe};
+use std::process;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{mpsc, Arc, Mutex, PoisonError};
 use std::thread;
 use std::time::{Duration, Instant};
 use tui::{backend::CrosstermBackend, Terminal};
 
 enum EventType<I> {
     Input(I),
+    Paste(String),
+    Mouse(MouseEvent),
+    Resize,
     Tick,
+    // The terminal couldn't be read, which ends the TUI
+    InputError(io::Error),
+}
+
+// Lets the main thread stop the input thread from reading events while
+// another program has the terminal
+#[derive(Default)]
+struct InputPause {
+    paused: AtomicBool,
+    reading: Mutex<()>,
 }
 
 fn main() -> Result<(), Box<dyn Error>> {
-    // Initialize terminal
+    let args: Vec<String> = env::args().skip(1).collect();
+    match args.first().map(String::as_str) {
+        Some("run") => match cli::run(&args[1..]) {
+            Ok(true) => process::exit(0),
+            Ok(false) => process::exit(1),
+            Err(e) => {
+                eprintln!("error: {}", e);
+                process::exit(2);
+            }
+        },
+        Some("import") => match cli::import(&args[1..]) {
+            Ok(()) => process::exit(0),
+            Err(e) => {
+                eprintln!("error: {}", e);
+                process::exit(2);
+            }
+        },
+        _ => {}
+    }
+
+    // The TUI can be opened in another directory, with a request ready
+    let launch = match cli::parse_launch_args(&args) {
+        Ok(launch) => launch,
+        Err(e) => {
+            eprintln!("{}", e);
+            process::exit(2);
+        }
+    };
+    if let Some(workspace) = &launch.workspace {
+        if let Err(e) = env::set_current_dir(workspace) {
+            eprintln!(
+                "error: could not open workspace {}: {}",
+                workspace.display(),
+                e
+            );
+            process::exit(2);
+        }
+    }
+
+    // Scripts and pipes get the response on stdout rather than the TUI
+    if launch.no_tui || !io::stdout().is_terminal() {
+        match cli::send(&launch) {
+            Ok(code) => process::exit(code),
+            Err(e) => {
+                eprintln!("error: {}", e);
+                process::exit(2);
+            }
+        }
+    }
+
+    // Initialize application
+    let mut app = App::new();
+    if let Err(e) = app.launch(launch) {
+        eprintln!("error: {}", e);
+        process::exit(2);
+    }
+
+    // Initialize terminal. However the TUI ends, even by panicking, the
+    // terminal is restored.
+    crash::install_panic_hook();
     enable_raw_mode()?;
+    let _guard = crash::TerminalGuard;
     let mut stdout = io::stdout();
-    execute!(stdout, EnterAlternateScreen)?;
+    execute!(
+        stdout,
+        EnterAlternateScreen,
+        EnableBracketedPaste,
+        EnableMouseCapture
+    )?;
     let backend = CrosstermBackend::new(stdout);
     let mut terminal = Terminal::new(backend)?;
     terminal.clear()?;
@@ -33,97 +130,177 @@ fn main() -> Result<(), Box<dyn Error>> {
     // Input handling thread
     let (tx, rx) = mpsc::channel();
     let tick_rate = Duration::from_millis(200);
+    let input_pause = Arc::new(InputPause::default());
+    let thread_pause = Arc::clone(&input_pause);
     thread::spawn(move || {
         let mut last_tick = Instant::now();
         loop {
+            if thread_pause.paused.load(Ordering::SeqCst) {
+                thread::sleep(Duration::from_millis(20));
+                continue;
+            }
+            let _reading = thread_pause
+                .reading
+                .lock()
+                .unwrap_or_else(PoisonError::into_inner);
+
             let timeout = tick_rate
                 .checked_sub(last_tick.elapsed())
                 .unwrap_or_else(|| Duration::from_secs(0));
 
-            if event::poll(timeout).unwrap() {
-                if let event::Event::Key(key) = event::read().unwrap() {
-                    tx.send(EventType::Input(key)).unwrap();
+            let read = event::poll(timeout).and_then(|ready| {
+                if ready {
+                    event::read().map(Some)
+                } else {
+                    Ok(None)
                 }
-            }
+            });
+            let event = match read {
+                Ok(Some(event::Event::Key(key))) => Some(EventType::Input(key)),
+                Ok(Some(event::Event::Paste(text))) => Some(EventType::Paste(text)),
+                // Plain mouse movement would only cause redraws
+                Ok(Some(event::Event::Mouse(mouse))) if mouse.kind != MouseEventKind::Moved => {
+                    Some(EventType::Mouse(mouse))
+                }
+                Ok(Some(event::Event::Resize(_, _))) => Some(EventType::Resize),
+                Ok(_) => None,
+                Err(e) => {
+                    let _ = tx.send(EventType::InputError(e));
+                    return;
+                }
+            };
 
-            if last_tick.elapsed() >= tick_rate && tx.send(EventType::Tick).is_ok() {
+            // Once the main loop has stopped listening, so can this thread
+            if let Some(event) = event {
+                if tx.send(event).is_err() {
+                    return;
+                }
+            }
+            if last_tick.elapsed() >= tick_rate {
+                if tx.send(EventType::Tick).is_err() {
+                    return;
+                }
                 last_tick = Instant::now();
             }
         }
     });
 
-    // Initialize application
-    let mut app = App::new();
+    // The session is saved even after a panic, so no work is lost
+    let result = panic::catch_unwind(AssertUnwindSafe(|| {
+        run(&mut app, &mut terminal, &rx, &input_pause)
+    }));
+    let saved = app.exit();
+    crash::restore_terminal();
+    if let Err(e) = saved {
+        eprintln!("could not save the session: {}", e);
+    }
+    match result {
+        Ok(result) => result,
+        Err(_) => {
+            eprintln!("Your tabs were saved, and will be restored next time.");
+            process::exit(101);
+        }
+    }
+}
 
-    // Main drawing loop
+// Draws the UI and handles events until the user quits
+fn run(
+    app: &mut App,
+    terminal: &mut Terminal<CrosstermBackend<io::Stdout>>,
+    rx: &mpsc::Receiver<EventType<KeyEvent>>,
+    input_pause: &InputPause,
+) -> Result<(), Box<dyn Error>> {
     loop {
         // Draw UI
-        app.draw(&mut terminal)?;
+        app.draw(terminal)?;
 
         // Input handling
         match rx.recv()? {
-            EventType::Input(key) => match app.input_mode {
-                app::InputMode::Navigation => match key.code {
-                    KeyCode::Esc => app.escape(),
-                    KeyCode::Char('q') => break,
-                    KeyCode::Enter => app.enter(),
-                    KeyCode::Char('h') | KeyCode::Left => app.left(),
-                    KeyCode::Char('l') | KeyCode::Right => app.right(),
-                    KeyCode::Char('k') | KeyCode::Up => app.up(),
-                    KeyCode::Char('j') | KeyCode::Down => app.down(),
-                    _ => {}
-                },
-                app::InputMode::Entry => match key.code {
-                    KeyCode::Esc => app.exit_input(),
-                    KeyCode::Enter => app.input_char('\n'), // TODO: try removing this line
-                    KeyCode::Backspace => app.backspace(),
-                    KeyCode::Tab => app.input_tab(),
-                    KeyCode::Left => app.entry_left(),
-                    KeyCode::Right => app.entry_right(),
-                    KeyCode::Up => app.entry_up(),
-                    KeyCode::Down => app.entry_down(),
-                    KeyCode::Char(c) => app.input_char(c),
-                    _ => {}
-                },
-                app::InputMode::TabSelect => match key.code {
-                    KeyCode::Char('q') => break,
-                    KeyCode::Esc => app.exit_input(),
-                    KeyCode::Char('h') | KeyCode::Left => app.tab_left(),
-                    KeyCode::Char('l') | KeyCode::Right => app.tab_right(),
-                    KeyCode::Backspace | KeyCode::Delete => app.tab_delete(),
-                    _ => {}
-                },
-                app::InputMode::EndpointEntry => match key.code {
-                    KeyCode::Esc | KeyCode::Enter => app.exit_input(),
-                    KeyCode::Backspace => app.endpoint_backspace(),
-                    KeyCode::Char(c) => app.endpoint_input_char(c),
-                    _ => {}
-                },
-                app::InputMode::BodyHeaderSelect => {
-                    if key.code == KeyCode::Esc {
-                        app.exit_input();
+            // The help scrolls, and any other key closes it
+            EventType::Input(key) if app.help_visible() => match app.keymap.press("help", key) {
+                Press::Action(action) => app.perform_help(action),
+                Press::Pending => {}
+                Press::Unbound(_) => app.close_help(),
+            },
+            EventType::Input(key) => {
+                let action = match app.keymap.press(app.input_mode.context(), key) {
+                    Press::Action(Action::Enter) if app.input_mode == InputMode::Palette => {
+                        app.palette_choice()
                     }
-                }
-                app::InputMode::MethodSelect => match key.code {
-                    KeyCode::Esc | KeyCode::Enter => app.exit_input(),
-                    KeyCode::Char('h') | KeyCode::Left => app.method_select_left(),
-                    KeyCode::Char('l') | KeyCode::Right => app.method_select_right(),
-                    _ => {}
-                },
-                app::InputMode::ResponseSelect => {
-                    if key.code == KeyCode::Esc {
-                        app.exit_input();
+                    Press::Action(action) => Some(action),
+                    Press::Pending => None,
+                    Press::Unbound(key) => {
+                        app.type_key(key);
+                        None
+                    }
+                };
+                match action {
+                    Some(Action::Quit) => break,
+                    Some(Action::ExternalEditor) => {
+                        let (text, extension) = app.external_edit_text();
+                        let result =
+                            suspend(terminal, input_pause, || editor::edit(&text, extension))?;
+                        app.finish_external_edit(result);
                     }
+                    Some(action) => app.perform(action),
+                    None => {}
                 }
+            }
+            EventType::Paste(text) => app.paste_text(&text),
+            EventType::Mouse(mouse) => match mouse.kind {
+                MouseEventKind::Down(MouseButton::Left) => app.click(mouse.column, mouse.row),
+                MouseEventKind::ScrollUp => app.scroll_at(mouse.column, mouse.row, -3),
+                MouseEventKind::ScrollDown => app.scroll_at(mouse.column, mouse.row, 3),
+                _ => {}
             },
-            EventType::Tick => {}
+            // Redrawn straight away, at the top of the loop
+            EventType::Resize => {}
+            EventType::Tick => app.tick(),
+            EventType::InputError(e) => {
+                return Err(format!("could not read from the terminal: {}", e).into())
+            }
         }
     }
 
-    // Cleanup and exit
-    app.exit();
+    Ok(())
+}
+
+// Hands the terminal to `f`, e.g. to run an external editor, restoring the
+// TUI afterwards
+fn suspend<T>(
+    terminal: &mut Terminal<CrosstermBackend<io::Stdout>>,
+    input_pause: &InputPause,
+    f: impl FnOnce() -> T,
+) -> Result<T, Box<dyn Error>> {
+    // Wait for the input thread to finish any read in progress
+    input_pause.paused.store(true, Ordering::SeqCst);
+    let reading = input_pause
+        .reading
+        .lock()
+        .unwrap_or_else(PoisonError::into_inner);
+
     disable_raw_mode()?;
+    execute!(
+        terminal.backend_mut(),
+        LeaveAlternateScreen,
+        DisableBracketedPaste,
+        DisableMouseCapture
+    )?;
     terminal.show_cursor()?;
-    execute!(terminal.backend_mut(), LeaveAlternateScreen)?;
-    Ok(())
+
+    let result = f();
+
+    enable_raw_mode()?;
+    execute!(
+        terminal.backend_mut(),
+        EnterAlternateScreen,
+        EnableBracketedPaste,
+        EnableMouseCapture
+    )?;
+    terminal.clear()?;
+    terminal.hide_cursor()?;
+
+    drop(reading);
+    input_pause.paused.store(false, Ordering::SeqCst);
+    Ok(result)
 }
@@ -0,0 +1,714 @@
+use crate::collection::{Collection, Folder};
+use crate::request::RequestSpec;
+use crate::variables::{Environment, Variables};
+use base64::{engine::general_purpose::STANDARD, Engine};
+use serde_json::{json, Value};
+
+// Converts Postman v2.1 collection and environment exports. Postman writes
+// variables as `{{name}}` too, so they're kept as they are. Anything with no
+// equivalent here, like scripts, is left out and listed in the summary.
+
+pub enum Import {
+    Collection(Collection),
+    Environment(Environment),
+}
+
+// How much was imported, and what couldn't be
+#[derive(Default)]
+pub struct Summary {
+    pub requests: usize,
+    pub folders: usize,
+    pub variables: usize,
+    // Where each thing left out was, and what it was
+    pub skipped: Vec<(String, String)>,
+}
+
+impl Summary {
+    fn skip(&mut self, location: &str, what: impl Into<String>) {
+        self.skipped.push((location.to_string(), what.into()));
+    }
+}
+
+// Imports a collection or an environment, whichever the export is
+pub fn import(text: &str) -> Result<(Import, Summary), String> {
+    let json: Value =
+        serde_json::from_str(text).map_err(|e| format!("invalid Postman export: {}", e))?;
+    if json.get("info").is_some() {
+        let (collection, summary) = import_collection(&json)?;
+        Ok((Import::Collection(collection), summary))
+    } else if json.get("values").is_some() {
+        let (environment, summary) = import_environment(&json)?;
+        Ok((Import::Environment(environment), summary))
+    } else {
+        Err("not a Postman collection or environment export".to_string())
+    }
+}
+
+pub fn import_collection(json: &Value) -> Result<(Collection, Summary), String> {
+    let info = json.get("info").ok_or("not a Postman collection")?;
+    let schema = text(info, "schema");
+    if !schema.is_empty() && !schema.contains("v2.1") {
+        return Err(format!(
+            "unsupported collection schema {}: export the collection as v2.1",
+            schema
+        ));
+    }
+
+    let mut summary = Summary::default();
+    let location = "collection";
+    skip_scripts(json, location, &mut summary);
+    let variables = import_variables(json, location, &mut summary);
+    let (requests, folders) = import_items(list(json, "item"), "", auth(json, None), &mut summary);
+
+    let collection = Collection {
+        name: text(info, "name"),
+        variables,
+        requests,
+        folders,
+        workflows: vec![],
+    };
+    Ok((collection, summary))
+}
+
+pub fn import_environment(json: &Value) -> Result<(Environment, Summary), String> {
+    let values = json
+        .get("values")
+        .and_then(Value::as_array)
+        .ok_or("not a Postman environment")?;
+    let name = text(json, "name");
+    let mut summary = Summary::default();
+    let mut variables = Variables::new();
+    for value in values {
+        let key = text(value, "key");
+        if value.get("enabled") == Some(&Value::Bool(false)) {
+            summary.skip(&name, format!("disabled variable {}", key));
+            continue;
+        }
+        variables.insert(key, text(value, "value"));
+    }
+    summary.variables = variables.len();
+    Ok((Environment { name, variables }, summary))
+}
+
+// A list of folders and requests. Requests inherit their folders' auth.
+fn import_items(
+    items: &[Value],
+    parent: &str,
+    inherited: Option<&Value>,
+    summary: &mut Summary,
+) -> (Vec<RequestSpec>, Vec<Folder>) {
+    let mut requests = vec![];
+    let mut folders = vec![];
+    for item in items {
+        let name = text(item, "name");
+        let location = if parent.is_empty() {
+            name.clone()
+        } else {
+            format!("{}/{}", parent, name)
+        };
+        skip_scripts(item, &location, summary);
+
+        if let Some(children) = item.get("item").and_then(Value::as_array) {
+            summary.folders += 1;
+            let variables = import_variables(item, &location, summary);
+            let (requests, children) =
+                import_items(children, &location, auth(item, inherited), summary);
+            folders.push(Folder {
+                name,
+                variables,
+                requests,
+                folders: children,
+            });
+        } else if let Some(request) = item.get("request") {
+            summary.requests += 1;
+            if !list(item, "response").is_empty() {
+                summary.skip(&location, "saved responses");
+            }
+            requests.push(import_request(name, request, &location, inherited, summary));
+        }
+    }
+    (requests, folders)
+}
+
+fn import_request(
+    name: String,
+    request: &Value,
+    location: &str,
+    inherited: Option<&Value>,
+    summary: &mut Summary,
+) -> RequestSpec {
+    // A request can be written as just its URL
+    if let Value::String(url) = request {
+        return RequestSpec {
+            name,
+            method: "GET".to_string(),
+            url: url.clone(),
+            headers: vec![],
+            query: vec![],
+            body: String::new(),
+            assertions: vec![],
+            extract: vec![],
+        };
+    }
+
+    let (url, mut query) = import_url(request.get("url"));
+    let mut headers = import_headers(request.get("header"));
+    let body = import_body(request.get("body"), &mut headers, location, summary);
+    apply_auth(
+        auth(request, inherited),
+        &mut headers,
+        &mut query,
+        location,
+        summary,
+    );
+
+    let method = text(request, "method").to_ascii_uppercase();
+    let request = RequestSpec {
+        name,
+        method: if method.is_empty() {
+            "GET".to_string()
+        } else {
+            method
+        },
+        url,
+        headers,
+        query,
+        body,
+        assertions: vec![],
+        extract: vec![],
+    };
+    for name in request.references() {
+        if name.starts_with('$') {
+            summary.skip(location, format!("dynamic variable {{{{{}}}}}", name));
+        }
+    }
+    request
+}
+
+// The URL without its query, which goes in the query pairs. Path variables
+// like `:id` are filled in with their values, or made into `{{id}}`.
+fn import_url(url: Option<&Value>) -> (String, Vec<(String, String)>) {
+    let url = match url {
+        Some(Value::String(url)) => return (url.clone(), vec![]),
+        Some(url) => url,
+        None => return (String::new(), vec![]),
+    };
+
+    let mut raw = text(url, "raw");
+    if raw.is_empty() {
+        raw = build_url(url);
+    }
+    let query = url.get("query").and_then(Value::as_array);
+    if query.is_some() {
+        raw.truncate(raw.find('?').unwrap_or(raw.len()));
+    }
+
+    for variable in list(url, "variable") {
+        let key = text(variable, "key");
+        let value = match text(variable, "value") {
+            value if value.is_empty() => format!("{{{{{}}}}}", key),
+            value => value,
+        };
+        let placeholder = format!(":{}", key);
+        raw = raw
+            .split('/')
+            .map(|segment| {
+                if segment == placeholder {
+                    value.as_str()
+                } else {
+                    segment
+                }
+            })
+            .collect::<Vec<_>>()
+            .join("/");
+    }
+
+    (raw, pairs(query.map_or(&[][..], Vec::as_slice)))
+}
+
+// Older exports can leave out `raw` and only have the URL's parts
+fn build_url(url: &Value) -> String {
+    let parts = |key: &str| match url.get(key) {
+        Some(Value::Array(parts)) => parts.iter().map(string).collect::<Vec<_>>(),
+        Some(part) => vec![string(part)],
+        None => vec![],
+    };
+    let mut built = String::new();
+    let protocol = text(url, "protocol");
+    if !protocol.is_empty() {
+        built.push_str(&format!("{}://", protocol));
+    }
+    built.push_str(&parts("host").join("."));
+    let port = text(url, "port");
+    if !port.is_empty() {
+        built.push_str(&format!(":{}", port));
+    }
+    for segment in parts("path") {
+        built.push('/');
+        built.push_str(&segment);
+    }
+    built
+}
+
+// Headers are a list, or occasionally a block of `Name: value` lines
+fn import_headers(headers: Option<&Value>) -> Vec<(String, String)> {
+    match headers {
+        Some(Value::String(lines)) => lines
+            .lines()
+            .filter_map(|line| line.split_once(':'))
+            .map(|(name, value)| (name.trim().to_string(), value.trim().to_string()))
+            .collect(),
+        Some(Value::Array(headers)) => pairs(headers),
+        _ => vec![],
+    }
+}
+
+// The body as text, adding the Content-Type Postman would send with it
+fn import_body(
+    body: Option<&Value>,
+    headers: &mut Vec<(String, String)>,
+    location: &str,
+    summary: &mut Summary,
+) -> String {
+    let body = match body {
+        Some(body) if body.get("disabled") != Some(&Value::Bool(true)) => body,
+        _ => return String::new(),
+    };
+
+    let (text_body, content_type) = match text(body, "mode").as_str() {
+        "raw" => {
+            let language = body
+                .pointer("/options/raw/language")
+                .map_or_else(String::new, string);
+            let content_type = match language.as_str() {
+                "json" => Some("application/json"),
+                "xml" => Some("application/xml"),
+                "html" => Some("text/html"),
+                "javascript" => Some("application/javascript"),
+                "text" => Some("text/plain"),
+                _ => None,
+            };
+            (text(body, "raw"), content_type)
+        }
+        "urlencoded" => (
+            form_body(&pairs(list(body, "urlencoded"))),
+            Some("application/x-www-form-urlencoded"),
+        ),
+        "formdata" => {
+            let mut fields = vec![];
+            for field in list(body, "formdata") {
+                if field.get("disabled") == Some(&Value::Bool(true)) {
+                    continue;
+                }
+                if text(field, "type") == "file" {
+                    summary.skip(
+                        location,
+                        format!("form-data file field \"{}\"", text(field, "key")),
+                    );
+                } else {
+                    fields.push((text(field, "key"), text(field, "value")));
+                }
+            }
+            summary.skip(location, "multipart form-data (sent as urlencoded)");
+            (
+                form_body(&fields),
+                Some("application/x-www-form-urlencoded"),
+            )
+        }
+        "graphql" => {
+            let graphql = body.get("graphql").cloned().unwrap_or(Value::Null);
+            let variables = text(&graphql, "variables");
+            let variables = if variables.trim().is_empty() {
+                json!({})
+            } else {
+                serde_json::from_str(&variables).unwrap_or_else(|_| {
+                    summary.skip(location, "GraphQL variables that aren't valid JSON");
+                    json!({})
+                })
+            };
+            let body = json!({ "query": text(&graphql, "query"), "variables": variables });
+            (body.to_string(), Some("application/json"))
+        }
+        "file" => {
+            summary.skip(location, "file body");
+            (String::new(), None)
+        }
+        "" | "none" => (String::new(), None),
+        mode => {
+            summary.skip(location, format!("{} body", mode));
+            (String::new(), None)
+        }
+    };
+
+    if let Some(content_type) = content_type.filter(|_| !text_body.is_empty()) {
+        add_header(headers, "Content-Type", content_type.to_string());
+    }
+    text_body
+}
+
+// Auth becomes the header (or query parameter) it would send
+fn apply_auth(
+    auth: Option<&Value>,
+    headers: &mut Vec<(String, String)>,
+    query: &mut Vec<(String, String)>,
+    location: &str,
+    summary: &mut Summary,
+) {
+    let auth = match auth {
+        Some(auth) => auth,
+        None => return,
+    };
+    let kind = text(auth, "type");
+    let setting = |key: &str| {
+        list(auth, &kind)
+            .iter()
+            .find(|setting| text(setting, "key") == key)
+            .map_or_else(String::new, |setting| text(setting, "value"))
+    };
+
+    match kind.as_str() {
+        "noauth" => {}
+        "bearer" => add_header(
+            headers,
+            "Authorization",
+            format!("Bearer {}", setting("token")),
+        ),
+        "basic" => {
+            let credentials = format!("{}:{}", setting("username"), setting("password"));
+            // The header is encoded now, so it can't use variables
+            if credentials.contains("{{") {
+                summary.skip(location, "basic auth using variables");
+            } else {
+                let encoded = STANDARD.encode(credentials);
+                add_header(headers, "Authorization", format!("Basic {}", encoded));
+            }
+        }
+        "apikey" => {
+            let (key, value) = (setting("key"), setting("value"));
+            if setting("in") == "query" {
+                query.push((key, value));
+            } else {
+                add_header(headers, &key, value);
+            }
+        }
+        kind => summary.skip(location, format!("{} auth", kind)),
+    }
+}
+
+// The auth a request or folder uses, if it doesn't inherit its parent's
+fn auth<'a>(item: &'a Value, inherited: Option<&'a Value>) -> Option<&'a Value> {
+    match item.get("auth") {
+        Some(auth) if !auth.is_null() && text(auth, "type") != "inherit" => Some(auth),
+        _ => inherited,
+    }
+}
+
+fn import_variables(item: &Value, location: &str, summary: &mut Summary) -> Variables {
+    let mut variables = Variables::new();
+    for variable in list(item, "variable") {
+        let key = text(variable, "key");
+        if variable.get("disabled") == Some(&Value::Bool(true)) {
+            summary.skip(location, format!("disabled variable {}", key));
+            continue;
+        }
+        variables.insert(key, text(variable, "value"));
+    }
+    summary.variables += variables.len();
+    variables
+}
+
+// Scripts can't be run here, so they're only reported
+fn skip_scripts(item: &Value, location: &str, summary: &mut Summary) {
+    for event in list(item, "event") {
+        let exec = event.pointer("/script/exec");
+        let script = match exec {
+            Some(Value::Array(lines)) => lines.iter().map(string).collect::<Vec<_>>().join("\n"),
+            Some(exec) => string(exec),
+            None => String::new(),
+        };
+        if script.trim().is_empty() {
+            continue;
+        }
+        let kind = match text(event, "listen").as_str() {
+            "prerequest" => "pre-request".to_string(),
+            listen => listen.to_string(),
+        };
+        summary.skip(location, format!("{} script", kind));
+    }
+}
+
+// `key`/`value` lists, leaving out the disabled entries
+fn pairs(list: &[Value]) -> Vec<(String, String)> {
+    list.iter()
+        .filter(|pair| pair.get("disabled") != Some(&Value::Bool(true)))
+        .map(|pair| (text(pair, "key"), text(pair, "value")))
+        .collect()
+}
+
+fn add_header(headers: &mut Vec<(String, String)>, name: &str, value: String) {
+    if !headers.iter().any(|(h, _)| h.eq_ignore_ascii_case(name)) {
+        headers.push((name.to_string(), value));
+    }
+}
+
+// Form fields, percent-encoded apart from any `{{variables}}` in them
+fn form_body(fields: &[(String, String)]) -> String {
+    fields
+        .iter()
+        .map(|(key, value)| format!("{}={}", form_encode(key), form_encode(value)))
+        .collect::<Vec<_>>()
+        .join("&")
+}
+
+fn form_encode(text: &str) -> String {
+    let mut encoded = String::new();
+    let mut rest = text;
+    while !rest.is_empty() {
+        if rest.starts_with("{{") {
+            if let Some(end) = rest.find("}}") {
+                encoded.push_str(&rest[..end + 2]);
+                rest = &rest[end + 2..];
+                continue;
+            }
+        }
+        let c = rest.chars().next().unwrap();
+        match c {
+            'A'..='Z' | 'a'..='z' | '0'..='9' | '-' | '.' | '_' | '~' => encoded.push(c),
+            ' ' => encoded.push('+'),
+            _ => {
+                let mut bytes = [0; 4];
+                for byte in c.encode_utf8(&mut bytes).bytes() {
+                    encoded.push_str(&format!("%{:02X}", byte));
+                }
+            }
+        }
+        rest = &rest[c.len_utf8()..];
+    }
+    encoded
+}
+
+fn list<'a>(value: &'a Value, key: &str) -> &'a [Value] {
+    value
+        .get(key)
+        .and_then(Value::as_array)
+        .map_or(&[], Vec::as_slice)
+}
+
+fn text(value: &Value, key: &str) -> String {
+    value.get(key).map_or_else(String::new, string)
+}
+
+// Values are usually strings, but numbers and booleans turn up too
+fn string(value: &Value) -> String {
+    match value {
+        Value::String(text) => text.clone(),
+        Value::Null => String::new(),
+        value => value.to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn import_collection(json: Value) -> (Collection, Summary) {
+        match import(&json.to_string()) {
+            Ok((Import::Collection(collection), summary)) => (collection, summary),
+            _ => panic!("not imported as a collection"),
+        }
+    }
+
+    fn pair(name: &str, value: &str) -> (String, String) {
+        (name.to_string(), value.to_string())
+    }
+
+    #[test]
+    fn imports_a_collection() {
+        let (collection, summary) = import_collection(json!({
+            "info": {
+                "name": "API",
+                "schema": "https://schema.getpostman.com/json/collection/v2.1.0/collection.json"
+            },
+            "variable": [{"key": "host", "value": "a.test"}],
+            "auth": {"type": "bearer", "bearer": [{"key": "token", "value": "{{token}}"}]},
+            "item": [
+                {
+                    "name": "Users",
+                    "item": [{
+                        "name": "Get user",
+                        "request": {
+                            "method": "get",
+                            "url": {
+                                "raw": "https://{{host}}/users/:id?full=1",
+                                "query": [
+                                    {"key": "full", "value": "1"},
+                                    {"key": "debug", "value": "1", "disabled": true}
+                                ],
+                                "variable": [{"key": "id", "value": "42"}]
+                            }
+                        }
+                    }]
+                },
+                {
+                    "name": "Create",
+                    "request": {
+                        "method": "POST",
+                        "auth": {"type": "noauth"},
+                        "header": [{"key": "X-Id", "value": "7"}],
+                        "url": "https://{{host}}/users",
+                        "body": {
+                            "mode": "raw",
+                            "raw": "{\"name\":\"Ann\"}",
+                            "options": {"raw": {"language": "json"}}
+                        }
+                    }
+                },
+                {"name": "Home", "request": "https://a.test/"}
+            ]
+        }));
+
+        assert_eq!(collection.name, "API");
+        assert_eq!(collection.variables.get("host").unwrap(), "a.test");
+        assert_eq!(summary.requests, 3);
+        assert_eq!(summary.folders, 1);
+        assert_eq!(summary.variables, 1);
+        assert!(summary.skipped.is_empty());
+
+        let get = &collection.folders[0].requests[0];
+        assert_eq!(get.method, "GET");
+        assert_eq!(get.url, "https://{{host}}/users/42");
+        assert_eq!(get.query, [pair("full", "1")]);
+        assert_eq!(get.headers, [pair("Authorization", "Bearer {{token}}")]);
+
+        let create = &collection.requests[0];
+        assert_eq!(
+            create.headers,
+            [pair("X-Id", "7"), pair("Content-Type", "application/json")]
+        );
+        assert_eq!(create.body, r#"{"name":"Ann"}"#);
+        assert_eq!(collection.requests[1].url, "https://a.test/");
+    }
+
+    #[test]
+    fn imports_bodies_and_auth() {
+        let (collection, _) = import_collection(json!({
+            "info": {"name": "Bodies"},
+            "item": [
+                {
+                    "name": "Form",
+                    "request": {
+                        "method": "POST",
+                        "url": "https://a.test",
+                        "auth": {"type": "basic", "basic": [
+                            {"key": "username", "value": "ann"},
+                            {"key": "password", "value": "secret"}
+                        ]},
+                        "body": {"mode": "urlencoded", "urlencoded": [
+                            {"key": "q", "value": "a b&c"},
+                            {"key": "id", "value": "{{id}}"}
+                        ]}
+                    }
+                },
+                {
+                    "name": "Query",
+                    "request": {
+                        "method": "POST",
+                        "url": "https://a.test/graphql",
+                        "auth": {"type": "apikey", "apikey": [
+                            {"key": "key", "value": "api_key"},
+                            {"key": "value", "value": "k"},
+                            {"key": "in", "value": "query"}
+                        ]},
+                        "body": {"mode": "graphql", "graphql": {
+                            "query": "{ me { id } }",
+                            "variables": "{\"a\": 1}"
+                        }}
+                    }
+                }
+            ]
+        }));
+
+        let form = &collection.requests[0];
+        assert_eq!(form.body, "q=a+b%26c&id={{id}}");
+        assert_eq!(
+            form.headers,
+            [
+                pair("Content-Type", "application/x-www-form-urlencoded"),
+                pair("Authorization", "Basic YW5uOnNlY3JldA=="),
+            ]
+        );
+
+        let query = &collection.requests[1];
+        assert_eq!(query.query, [pair("api_key", "k")]);
+        let body: Value = serde_json::from_str(&query.body).unwrap();
+        assert_eq!(
+            body,
+            json!({"query": "{ me { id } }", "variables": {"a": 1}})
+        );
+    }
+
+    #[test]
+    fn reports_what_was_left_out() {
+        let (_, summary) = import_collection(json!({
+            "info": {"name": "Skips"},
+            "event": [{"listen": "prerequest", "script": {"exec": ["pm.environment.set('a', 1)"]}}],
+            "item": [{
+                "name": "Upload",
+                "event": [{"listen": "test", "script": {"exec": [""]}}],
+                "response": [{"name": "example"}],
+                "request": {
+                    "url": "https://a.test/{{$guid}}",
+                    "auth": {"type": "oauth2"},
+                    "body": {"mode": "file", "file": {"src": "a.png"}}
+                }
+            }]
+        }));
+
+        let skipped: Vec<String> = summary
+            .skipped
+            .iter()
+            .map(|(location, what)| format!("{}: {}", location, what))
+            .collect();
+        assert_eq!(
+            skipped,
+            [
+                "collection: pre-request script",
+                "Upload: saved responses",
+                "Upload: file body",
+                "Upload: oauth2 auth",
+                "Upload: dynamic variable {{$guid}}",
+            ]
+        );
+    }
+
+    #[test]
+    fn imports_an_environment() {
+        let text = json!({
+            "name": "Local",
+            "values": [
+                {"key": "host", "value": "localhost", "enabled": true},
+                {"key": "port", "value": 8080},
+                {"key": "old", "value": "x", "enabled": false}
+            ]
+        })
+        .to_string();
+        let (environment, summary) = match import(&text) {
+            Ok((Import::Environment(environment), summary)) => (environment, summary),
+            _ => panic!("not imported as an environment"),
+        };
+        assert_eq!(environment.name, "Local");
+        assert_eq!(environment.variables.get("port").unwrap(), "8080");
+        assert!(!environment.variables.contains_key("old"));
+        assert_eq!(summary.variables, 2);
+        assert_eq!(summary.skipped, [pair("Local", "disabled variable old")]);
+    }
+
+    #[test]
+    fn rejects_other_files() {
+        assert!(import("not json").is_err());
+        assert!(import("{\"requests\": []}").is_err());
+        let old =
+            json!({"info": {"schema": "https://schema.getpostman.com/json/collection/v2.0.0/"}});
+        assert!(import(&old.to_string()).is_err());
+    }
+}
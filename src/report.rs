@@ -0,0 +1,124 @@
+use crate::runner::RunReport;
+use std::error::Error;
+use std::fmt::Write as _;
+use std::fs;
+use std::path::Path;
+
+pub fn write_json(report: &RunReport, path: &Path) -> Result<(), Box<dyn Error>> {
+    fs::write(path, serde_json::to_string_pretty(report)?)?;
+    Ok(())
+}
+
+// Each request becomes a test case. Requests that couldn't be sent are
+// errors, requests with failing assertions are failures and workflow steps
+// that never ran are skipped.
+pub fn write_junit(report: &RunReport, path: &Path) -> Result<(), Box<dyn Error>> {
+    let errors = report
+        .results
+        .iter()
+        .filter(|result| result.error.is_some())
+        .count();
+    let failures = report.failures() - errors;
+    let skipped = report
+        .results
+        .iter()
+        .filter(|result| result.skipped)
+        .count();
+    let name = escape(&report.collection);
+    let time = seconds(report.duration_ms);
+
+    let mut xml = String::from("<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n");
+    writeln!(
+        xml,
+        "<testsuites name=\"{}\" tests=\"{}\" failures=\"{}\" errors=\"{}\" time=\"{}\">",
+        name,
+        report.results.len(),
+        failures,
+        errors,
+        time
+    )?;
+    writeln!(
+        xml,
+        "  <testsuite name=\"{}\" tests=\"{}\" failures=\"{}\" errors=\"{}\" skipped=\"{}\" time=\"{}\">",
+        name,
+        report.results.len(),
+        failures,
+        errors,
+        skipped,
+        time
+    )?;
+
+    for result in &report.results {
+        let (classname, test_name) = match result.path.rfind('/') {
+            Some(i) => (&result.path[..i], &result.path[i + 1..]),
+            None => (report.collection.as_str(), result.path.as_str()),
+        };
+        writeln!(
+            xml,
+            "    <testcase classname=\"{}\" name=\"{}\" time=\"{}\">",
+            escape(classname),
+            escape(test_name),
+            seconds(result.duration_ms)
+        )?;
+
+        if result.skipped {
+            writeln!(xml, "      <skipped/>")?;
+        } else if let Some(error) = &result.error {
+            writeln!(
+                xml,
+                "      <error message=\"{}\">{} {}</error>",
+                escape(error),
+                escape(&result.method),
+                escape(&result.url)
+            )?;
+        } else if !result.passed() {
+            let failed: Vec<_> = result
+                .assertions
+                .iter()
+                .filter(|assertion| !assertion.passed)
+                .collect();
+            writeln!(
+                xml,
+                "      <failure message=\"{} of {} assertions failed\">",
+                failed.len(),
+                result.assertions.len()
+            )?;
+            for assertion in failed {
+                writeln!(
+                    xml,
+                    "{} ({})",
+                    escape(&assertion.description),
+                    escape(&assertion.message)
+                )?;
+            }
+            writeln!(xml, "      </failure>")?;
+        }
+
+        writeln!(xml, "    </testcase>")?;
+    }
+
+    writeln!(xml, "  </testsuite>")?;
+    writeln!(xml, "</testsuites>")?;
+    fs::write(path, xml)?;
+    Ok(())
+}
+
+fn seconds(ms: u128) -> String {
+    format!("{:.3}", ms as f64 / 1000.0)
+}
+
+fn escape(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&apos;"),
+            c if c.is_control() && c != '\n' && c != '\t' => {}
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
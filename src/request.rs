@@ -0,0 +1,168 @@
+use crate::assertion::Assertion;
+use crate::extraction::Extraction;
+use crate::timing::Timings;
+use crate::variables::{self, Variables};
+use reqwest::{blocking::Client, Method, Url};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeSet;
+use std::error::Error;
+use std::time::Instant;
+
+#[derive(Clone, Serialize, Deserialize)]
+pub struct RequestSpec {
+    #[serde(default)]
+    pub name: String,
+    #[serde(default = "default_method")]
+    pub method: String,
+    pub url: String,
+    #[serde(default)]
+    pub headers: Vec<(String, String)>,
+    #[serde(default)]
+    pub query: Vec<(String, String)>,
+    #[serde(default)]
+    pub body: String,
+    #[serde(default)]
+    pub assertions: Vec<Assertion>,
+    #[serde(default)]
+    pub extract: Vec<Extraction>,
+}
+
+fn default_method() -> String {
+    "GET".to_string()
+}
+
+#[derive(Clone, Serialize, Deserialize)]
+pub struct ResponseRecord {
+    pub status: u16,
+    pub headers: Vec<(String, String)>,
+    pub body: String,
+    pub timings: Timings,
+}
+
+impl RequestSpec {
+    // A copy of the request with every `{{variable}}` that has a value
+    // filled in
+    pub fn resolve(&self, variables: &Variables) -> RequestSpec {
+        let pairs = |pairs: &[(String, String)]| -> Vec<(String, String)> {
+            pairs
+                .iter()
+                .map(|(name, value)| {
+                    (
+                        variables::substitute(name, variables),
+                        variables::substitute(value, variables),
+                    )
+                })
+                .collect()
+        };
+
+        RequestSpec {
+            url: variables::substitute(&self.url, variables),
+            headers: pairs(&self.headers),
+            query: pairs(&self.query),
+            body: variables::substitute(&self.body, variables),
+            ..self.clone()
+        }
+    }
+
+    // Names of the variables the request uses that have no value
+    pub fn undefined(&self, variables: &Variables) -> Vec<String> {
+        self.references()
+            .into_iter()
+            .filter(|name| !variables.contains_key(name))
+            .collect()
+    }
+
+    // Names of the variables used anywhere in the request
+    pub fn references(&self) -> BTreeSet<String> {
+        let mut names = BTreeSet::new();
+        names.extend(variables::references(&self.url));
+        names.extend(variables::references(&self.body));
+        for (name, value) in self.headers.iter().chain(&self.query) {
+            names.extend(variables::references(name));
+            names.extend(variables::references(value));
+        }
+        names
+    }
+
+    // The URL with the query parameters added
+    pub fn full_url(&self) -> Result<Url, Box<dyn Error>> {
+        let mut url = Url::parse(&self.url)?;
+        if !self.query.is_empty() {
+            url.query_pairs_mut().extend_pairs(&self.query);
+        }
+        Ok(url)
+    }
+
+    // An equivalent curl command, to paste into a shell
+    pub fn to_curl(&self) -> Result<String, Box<dyn Error>> {
+        let mut command = format!(
+            "curl -X {} {}",
+            self.method,
+            shell_quote(self.full_url()?.as_str())
+        );
+        for (name, value) in &self.headers {
+            command.push_str(&format!(
+                " \\\n  -H {}",
+                shell_quote(&format!("{}: {}", name, value))
+            ));
+        }
+        if !self.body.is_empty() {
+            command.push_str(&format!(" \\\n  --data-raw {}", shell_quote(&self.body)));
+        }
+        Ok(command)
+    }
+
+    pub fn send(&self) -> Result<ResponseRecord, Box<dyn Error>> {
+        let url = self.full_url()?;
+        let client = Client::new();
+        let mut request = client.request(Method::from_bytes(self.method.as_bytes())?, url);
+        for (name, value) in &self.headers {
+            request = request.header(name.as_str(), value.as_str());
+        }
+        if !self.body.is_empty() {
+            request = request.body(self.body.clone());
+        }
+
+        let start = Instant::now();
+        let response = request.send()?;
+        let headers_received = start.elapsed();
+
+        let status = response.status().as_u16();
+        let headers = response
+            .headers()
+            .iter()
+            .map(|(name, value)| {
+                (
+                    name.to_string(),
+                    String::from_utf8_lossy(value.as_bytes()).into_owned(),
+                )
+            })
+            .collect();
+        let body = response.text()?;
+        let download = start.elapsed() - headers_received;
+
+        Ok(ResponseRecord {
+            status,
+            headers,
+            body,
+            timings: Timings {
+                connection: None,
+                first_byte: headers_received,
+                download,
+            },
+        })
+    }
+}
+
+impl ResponseRecord {
+    pub fn header(&self, name: &str) -> Option<&str> {
+        self.headers
+            .iter()
+            .find(|(header, _)| header.eq_ignore_ascii_case(name))
+            .map(|(_, value)| value.as_str())
+    }
+}
+
+fn shell_quote(text: &str) -> String {
+    format!("'{}'", text.replace('\'', "'\\''"))
+}
@@ -0,0 +1,155 @@
+use crate::assertion::AssertionResult;
+use crate::collection::{Collection, CollectionItem};
+use crate::executor::{self, Executor};
+use crate::variables::{Environment, Variables};
+use serde::Serialize;
+use std::time::Instant;
+
+#[derive(Serialize)]
+pub struct RunReport {
+    pub collection: String,
+    pub environment: Option<String>,
+    pub duration_ms: u128,
+    pub results: Vec<RequestResult>,
+}
+
+#[derive(Serialize)]
+pub struct RequestResult {
+    pub path: String,
+    pub method: String,
+    pub url: String,
+    pub status: Option<u16>,
+    pub duration_ms: u128,
+    pub error: Option<String>,
+    pub skipped: bool,
+    pub assertions: Vec<AssertionResult>,
+    pub warnings: Vec<String>,
+}
+
+// What to do with a request that uses variables that have no value
+#[derive(Clone, Copy, PartialEq)]
+pub enum Undefined {
+    // Send it with the placeholders as written, with a warning
+    Warn,
+    // Fail it without sending it
+    Fail,
+}
+
+impl RunReport {
+    pub fn failures(&self) -> usize {
+        self.results
+            .iter()
+            .filter(|result| !result.passed() && !result.skipped)
+            .count()
+    }
+}
+
+impl RequestResult {
+    pub fn passed(&self) -> bool {
+        !self.skipped
+            && self.error.is_none()
+            && self.assertions.iter().all(|assertion| assertion.passed)
+    }
+}
+
+// Sends every request in the collection (or one of its folders) in order,
+// calling `on_result` as each one finishes. Values extracted from a response
+// are available to the requests after it.
+pub fn run(
+    collection: &Collection,
+    folder: Option<&str>,
+    environment: Option<&Environment>,
+    undefined: Undefined,
+    mut on_result: impl FnMut(&RequestResult),
+) -> Result<RunReport, String> {
+    let start = Instant::now();
+    let mut results = vec![];
+    let mut executor = Executor::new(environment);
+
+    for item in collection.items(folder)? {
+        let result = run_item(
+            &item,
+            &mut executor.environment,
+            &mut executor.runtime,
+            undefined,
+        );
+        on_result(&result);
+        results.push(result);
+    }
+
+    Ok(RunReport {
+        collection: collection.name.clone(),
+        environment: environment.map(|environment| environment.name.clone()),
+        duration_ms: start.elapsed().as_millis(),
+        results,
+    })
+}
+
+pub fn run_item(
+    item: &CollectionItem,
+    environment: &mut Variables,
+    runtime: &mut Variables,
+    undefined: Undefined,
+) -> RequestResult {
+    let variables = executor::variables(&item.variables, environment, runtime);
+    let mut result = RequestResult {
+        path: item.path.clone(),
+        method: item.request.method.clone(),
+        url: item.request.url.clone(),
+        status: None,
+        duration_ms: 0,
+        error: None,
+        skipped: false,
+        assertions: vec![],
+        warnings: vec![],
+    };
+
+    let missing = item.request.undefined(&variables);
+    if !missing.is_empty() {
+        match undefined {
+            Undefined::Warn => result.warnings.push(format!(
+                "undefined variables sent as written: {}",
+                missing.join(", ")
+            )),
+            Undefined::Fail => {
+                result.error = Some(format!("undefined variables: {}", missing.join(", ")));
+                return result;
+            }
+        }
+    }
+    let request = item.request.resolve(&variables);
+    result.url = request.url.clone();
+
+    match executor::send(request, environment, runtime) {
+        Ok(exchange) => {
+            result.status = Some(exchange.response.status);
+            result.duration_ms = exchange.response.timings.total().as_millis();
+            result.assertions = exchange.results;
+        }
+        Err(e) => result.error = Some(e.to_string()),
+    }
+    result
+}
+
+// A request that couldn't be run at all
+pub fn failed(item: &CollectionItem, error: String) -> RequestResult {
+    RequestResult {
+        error: Some(error),
+        skipped: false,
+        ..skipped(item)
+    }
+}
+
+pub fn skipped(item: &CollectionItem) -> RequestResult {
+    RequestResult {
+        path: item.path.clone(),
+        method: item.request.method.clone(),
+        url: item.request.url.clone(),
+        status: None,
+        duration_ms: 0,
+        error: None,
+        skipped: true,
+        assertions: vec![],
+        warnings: vec![],
+    }
+}
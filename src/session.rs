@@ -0,0 +1,155 @@
+use crate::config;
+use crate::history::HistoryEntry;
+use http_request_tool::assertion::AssertionResult;
+use http_request_tool::variables::{Environment, Variables};
+use serde::{Deserialize, Serialize};
+use std::env;
+use std::error::Error;
+use std::fs::{self, OpenOptions};
+use std::io::Write;
+#[cfg(unix)]
+use std::os::unix::fs::OpenOptionsExt;
+use std::path::PathBuf;
+
+// Everything in a tab: the request as it's written in the editors, which
+// tabs are selected, and the responses it got
+#[derive(Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct TabState {
+    pub endpoint: String,
+    pub method: String,
+    pub headers: String,
+    pub query: String,
+    pub body: String,
+    pub tests: String,
+    pub capture: String,
+    pub request_tab: String,
+    pub response_tab: String,
+    pub response_body: String,
+    pub test_results: Vec<AssertionResult>,
+    pub history: Vec<HistoryEntry>,
+    // Variables of the collection request it was opened from
+    pub variables: Variables,
+}
+
+impl Default for TabState {
+    fn default() -> TabState {
+        TabState {
+            endpoint: "http://".to_string(),
+            method: "GET".to_string(),
+            headers: String::new(),
+            query: String::new(),
+            body: String::new(),
+            tests: String::new(),
+            capture: String::new(),
+            request_tab: "BODY".to_string(),
+            response_tab: "BODY".to_string(),
+            response_body: String::new(),
+            test_results: vec![],
+            history: vec![],
+            variables: Variables::new(),
+        }
+    }
+}
+
+impl TabState {
+    // Whether the tab's request hasn't been touched, so it can be reused
+    pub fn is_blank(&self) -> bool {
+        let default = TabState::default();
+        self.endpoint == default.endpoint
+            && self.method == default.method
+            && [
+                &self.headers,
+                &self.query,
+                &self.body,
+                &self.tests,
+                &self.capture,
+            ]
+            .iter()
+            .all(|text| text.is_empty())
+            && self.history.is_empty()
+    }
+}
+
+// The whole workspace, saved as it changes and when the program quits, and
+// restored the next time it's started in the same directory
+#[derive(Serialize, Deserialize, Default)]
+#[serde(default)]
+pub struct Session {
+    pub tabs: Vec<TabState>,
+    pub current_tab: usize,
+    pub focused_pane: usize,
+    pub environment: Environment,
+    pub runtime_variables: Variables,
+}
+
+impl Session {
+    pub fn load() -> Result<Option<Session>, Box<dyn Error>> {
+        let path = match path() {
+            Some(path) if path.is_file() => path,
+            _ => return Ok(None),
+        };
+        let text = fs::read_to_string(&path)?;
+        let session: Session = serde_json::from_str(&text)
+            .map_err(|e| format!("invalid session {}: {}", path.display(), e))?;
+        Ok(Some(session).filter(|session| !session.tabs.is_empty()))
+    }
+
+    // Takes the session as JSON, so callers can skip saving when it hasn't
+    // changed
+    pub fn write(json: &str) -> Result<(), Box<dyn Error>> {
+        let path = path().ok_or("no config directory")?;
+        fs::create_dir_all(path.parent().unwrap())?;
+        // Written to the side first, so a crash mid-write can't lose the
+        // last session
+        let temporary = path.with_extension("json.tmp");
+        // The session holds environment values and captured tokens, so only
+        // the user may read it. A leftover temporary file would keep its old
+        // mode, so it's removed first.
+        let _ = fs::remove_file(&temporary);
+        let mut options = OpenOptions::new();
+        options.write(true).create_new(true);
+        #[cfg(unix)]
+        options.mode(0o600);
+        options.open(&temporary)?.write_all(json.as_bytes())?;
+        fs::rename(temporary, path)?;
+        Ok(())
+    }
+}
+
+// `sessions/<directory>.json` in the config directory, one per working
+// directory
+fn path() -> Option<PathBuf> {
+    let directory = env::current_dir().ok()?;
+    Some(
+        config::config_dir()?
+            .join("sessions")
+            .join(file_name(&directory.to_string_lossy()) + ".json"),
+    )
+}
+
+// Anything but ASCII letters, digits, `-` and `.` is written as `_` and its
+// hex bytes, so no two directories share a file
+fn file_name(directory: &str) -> String {
+    directory
+        .bytes()
+        .map(|byte| match byte {
+            b'a'..=b'z' | b'A'..=b'Z' | b'0'..=b'9' | b'-' | b'.' => (byte as char).to_string(),
+            _ => format!("_{:02x}", byte),
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn names_each_directory_differently() {
+        assert_eq!(file_name("/home/ann/api-v2"), "_2fhome_2fann_2fapi-v2");
+        assert_ne!(file_name("/a-b"), file_name("/a_b"));
+        assert_ne!(file_name("/a b"), file_name("/a_b"));
+        assert_ne!(file_name("/a_2fb"), file_name("/a/b"));
+        assert_eq!(file_name("/é"), "_2f_c3_a9");
+    }
+}
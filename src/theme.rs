@@ -0,0 +1,321 @@
+use crate::config::Config;
+use std::env;
+use tui::style::Color;
+
+// The colours used to draw the UI
+#[derive(Clone)]
+pub struct Theme {
+    pub name: String,
+    // Pane borders: not focused, focused, and being typed in or used
+    pub idle: Color,
+    pub focused: Color,
+    pub editing: Color,
+    // The selected tab or list item
+    pub highlight: Color,
+    pub text: Color,
+    // Hints, gutters and other secondary text
+    pub dim: Color,
+    // Prompts, keys and the current line marker
+    pub accent: Color,
+    pub error: Color,
+    pub success: Color,
+    // The background of the line the response cursor is on
+    pub cursor_line: Color,
+    pub key: Color,
+    pub string: Color,
+    pub number: Color,
+    pub literal: Color,
+    pub variable: Color,
+    pub status_info: Color,
+    pub status_success: Color,
+    pub status_redirect: Color,
+    pub status_client_error: Color,
+    pub status_server_error: Color,
+}
+
+pub const BUILT_IN: [&str; 3] = ["dark", "light", "high-contrast"];
+
+// The names a theme's colours go by in config.json
+const COLOUR_NAMES: [&str; 20] = [
+    "idle",
+    "focused",
+    "editing",
+    "highlight",
+    "text",
+    "dim",
+    "accent",
+    "error",
+    "success",
+    "cursor-line",
+    "key",
+    "string",
+    "number",
+    "literal",
+    "variable",
+    "status-info",
+    "status-success",
+    "status-redirect",
+    "status-client-error",
+    "status-server-error",
+];
+
+impl Theme {
+    pub fn dark() -> Theme {
+        Theme {
+            name: "dark".to_string(),
+            idle: Color::Reset,
+            focused: Color::Yellow,
+            editing: Color::Red,
+            highlight: Color::Magenta,
+            text: Color::Reset,
+            dim: Color::DarkGray,
+            accent: Color::Yellow,
+            error: Color::Red,
+            success: Color::Green,
+            cursor_line: Color::DarkGray,
+            key: Color::Cyan,
+            string: Color::Green,
+            number: Color::Yellow,
+            literal: Color::Magenta,
+            variable: Color::LightBlue,
+            status_info: Color::Blue,
+            status_success: Color::Green,
+            status_redirect: Color::Cyan,
+            status_client_error: Color::Yellow,
+            status_server_error: Color::Red,
+        }
+    }
+
+    pub fn light() -> Theme {
+        Theme {
+            name: "light".to_string(),
+            idle: Color::Reset,
+            focused: Color::Blue,
+            editing: Color::Red,
+            highlight: Color::Magenta,
+            text: Color::Reset,
+            dim: Color::Gray,
+            accent: Color::Blue,
+            error: Color::Red,
+            success: Color::Green,
+            cursor_line: Color::Gray,
+            key: Color::Blue,
+            string: Color::Green,
+            number: Color::Magenta,
+            literal: Color::Red,
+            variable: Color::Cyan,
+            status_info: Color::Blue,
+            status_success: Color::Green,
+            status_redirect: Color::Cyan,
+            status_client_error: Color::Magenta,
+            status_server_error: Color::Red,
+        }
+    }
+
+    pub fn high_contrast() -> Theme {
+        Theme {
+            name: "high-contrast".to_string(),
+            idle: Color::White,
+            focused: Color::LightYellow,
+            editing: Color::LightRed,
+            highlight: Color::LightCyan,
+            text: Color::White,
+            dim: Color::Gray,
+            accent: Color::LightYellow,
+            error: Color::LightRed,
+            success: Color::LightGreen,
+            cursor_line: Color::Blue,
+            key: Color::LightCyan,
+            string: Color::LightGreen,
+            number: Color::LightYellow,
+            literal: Color::LightMagenta,
+            variable: Color::LightBlue,
+            status_info: Color::LightBlue,
+            status_success: Color::LightGreen,
+            status_redirect: Color::LightCyan,
+            status_client_error: Color::LightYellow,
+            status_server_error: Color::LightRed,
+        }
+    }
+
+    // A built-in theme, or one from the config built on top of one. Colours
+    // are cut down to the basic 16 if the terminal can't show any others.
+    pub fn named(name: &str, config: &Config) -> Result<Theme, String> {
+        let mut theme = match name {
+            "dark" => Theme::dark(),
+            "light" => Theme::light(),
+            "high-contrast" => Theme::high_contrast(),
+            _ => {
+                let colours = config
+                    .themes
+                    .get(name)
+                    .ok_or_else(|| format!("unknown theme: {}", name))?;
+                let base = colours.get("base").map_or("dark", String::as_str);
+                if !BUILT_IN.contains(&base) {
+                    return Err(format!("theme {}: unknown base theme {}", name, base));
+                }
+                let mut theme = Theme::named(base, config)?;
+                theme.name = name.to_string();
+                for (colour_name, value) in colours.iter().filter(|(key, _)| *key != "base") {
+                    let colour = parse_colour(value)
+                        .ok_or_else(|| format!("theme {}: invalid colour {}", name, value))?;
+                    *theme.colour_mut(colour_name).ok_or_else(|| {
+                        format!("theme {}: unknown colour name {}", name, colour_name)
+                    })? = colour;
+                }
+                theme
+            }
+        };
+
+        if !truecolor() {
+            for colour_name in COLOUR_NAMES.iter() {
+                if let Some(colour) = theme.colour_mut(colour_name) {
+                    *colour = to_16_colours(*colour);
+                }
+            }
+        }
+        Ok(theme)
+    }
+
+    // The built-in themes and the user's, in that order
+    pub fn names(config: &Config) -> Vec<String> {
+        BUILT_IN
+            .iter()
+            .map(|name| name.to_string())
+            .chain(config.themes.keys().cloned())
+            .collect()
+    }
+
+    fn colour_mut(&mut self, name: &str) -> Option<&mut Color> {
+        Some(match name {
+            "idle" => &mut self.idle,
+            "focused" => &mut self.focused,
+            "editing" => &mut self.editing,
+            "highlight" => &mut self.highlight,
+            "text" => &mut self.text,
+            "dim" => &mut self.dim,
+            "accent" => &mut self.accent,
+            "error" => &mut self.error,
+            "success" => &mut self.success,
+            "cursor-line" => &mut self.cursor_line,
+            "key" => &mut self.key,
+            "string" => &mut self.string,
+            "number" => &mut self.number,
+            "literal" => &mut self.literal,
+            "variable" => &mut self.variable,
+            "status-info" => &mut self.status_info,
+            "status-success" => &mut self.status_success,
+            "status-redirect" => &mut self.status_redirect,
+            "status-client-error" => &mut self.status_client_error,
+            "status-server-error" => &mut self.status_server_error,
+            _ => return None,
+        })
+    }
+
+    pub fn status(&self, status: u16) -> Color {
+        match status {
+            100..=199 => self.status_info,
+            200..=299 => self.status_success,
+            300..=399 => self.status_redirect,
+            400..=499 => self.status_client_error,
+            _ => self.status_server_error,
+        }
+    }
+}
+
+// Colours are written as names (`red`, `lightblue`, `default`), `#rrggbb`,
+// or a 256-colour palette index
+fn parse_colour(text: &str) -> Option<Color> {
+    let text = text.trim().to_ascii_lowercase();
+    if let Some(hex) = text.strip_prefix('#') {
+        if hex.len() != 6 {
+            return None;
+        }
+        let channel = |i: usize| u8::from_str_radix(&hex[i..i + 2], 16).ok();
+        return Some(Color::Rgb(channel(0)?, channel(2)?, channel(4)?));
+    }
+    if let Ok(index) = text.parse() {
+        return Some(Color::Indexed(index));
+    }
+    NAMED_COLOURS
+        .iter()
+        .find(|(name, _)| *name == text.replace(['-', '_', ' '], ""))
+        .map(|(_, colour)| *colour)
+}
+
+const NAMED_COLOURS: [(&str, Color); 19] = [
+    ("default", Color::Reset),
+    ("reset", Color::Reset),
+    ("black", Color::Black),
+    ("red", Color::Red),
+    ("green", Color::Green),
+    ("yellow", Color::Yellow),
+    ("blue", Color::Blue),
+    ("magenta", Color::Magenta),
+    ("cyan", Color::Cyan),
+    ("gray", Color::Gray),
+    ("darkgray", Color::DarkGray),
+    ("lightred", Color::LightRed),
+    ("lightgreen", Color::LightGreen),
+    ("lightyellow", Color::LightYellow),
+    ("lightblue", Color::LightBlue),
+    ("lightmagenta", Color::LightMagenta),
+    ("lightcyan", Color::LightCyan),
+    ("white", Color::White),
+    ("grey", Color::Gray),
+];
+
+// Terminals that can show any RGB colour say so in COLORTERM
+fn truecolor() -> bool {
+    env::var("COLORTERM").is_ok_and(|value| value == "truecolor" || value == "24bit")
+}
+
+// The 16 basic colours, with the usual xterm RGB values
+const BASIC_COLOURS: [(Color, (u8, u8, u8)); 16] = [
+    (Color::Black, (0, 0, 0)),
+    (Color::Red, (205, 0, 0)),
+    (Color::Green, (0, 205, 0)),
+    (Color::Yellow, (205, 205, 0)),
+    (Color::Blue, (0, 0, 238)),
+    (Color::Magenta, (205, 0, 205)),
+    (Color::Cyan, (0, 205, 205)),
+    (Color::Gray, (229, 229, 229)),
+    (Color::DarkGray, (127, 127, 127)),
+    (Color::LightRed, (255, 0, 0)),
+    (Color::LightGreen, (0, 255, 0)),
+    (Color::LightYellow, (255, 255, 0)),
+    (Color::LightBlue, (92, 92, 255)),
+    (Color::LightMagenta, (255, 0, 255)),
+    (Color::LightCyan, (0, 255, 255)),
+    (Color::White, (255, 255, 255)),
+];
+
+// The closest of the 16 basic colours to an RGB or 256-colour one
+fn to_16_colours(colour: Color) -> Color {
+    let (r, g, b) = match colour {
+        Color::Rgb(r, g, b) => (r, g, b),
+        Color::Indexed(index) if index < 16 => return BASIC_COLOURS[index as usize].0,
+        Color::Indexed(index) => indexed_rgb(index),
+        _ => return colour,
+    };
+    let distance = |(r2, g2, b2): (u8, u8, u8)| {
+        let d = |a: u8, b: u8| (a as i32 - b as i32).pow(2);
+        d(r, r2) + d(g, g2) + d(b, b2)
+    };
+    BASIC_COLOURS
+        .iter()
+        .min_by_key(|(_, rgb)| distance(*rgb))
+        .map_or(colour, |(basic, _)| *basic)
+}
+
+// The RGB value of an xterm 256-colour index above 15: a 6x6x6 cube, then a
+// ramp of greys
+fn indexed_rgb(index: u8) -> (u8, u8, u8) {
+    if index >= 232 {
+        let grey = 8 + (index - 232) * 10;
+        return (grey, grey, grey);
+    }
+    let index = index - 16;
+    let level = |n: u8| if n == 0 { 0 } else { 55 + n * 40 };
+    (level(index / 36), level(index / 6 % 6), level(index % 6))
+}
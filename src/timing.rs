@@ -0,0 +1,134 @@
+use reqwest::Url;
+use serde::{Deserialize, Serialize};
+use std::env;
+use std::error::Error;
+use std::net::{TcpStream, ToSocketAddrs};
+use std::time::{Duration, Instant};
+
+// The probe is only for timings, so it doesn't hold up an unreachable host
+// for long
+const CONNECT_TIMEOUT: Duration = Duration::from_secs(3);
+
+// How long a request took. Reqwest doesn't report what happens inside a
+// request, so only the wait for the response headers (which includes
+// connecting) and the download are measured on the request itself.
+#[derive(Clone, Copy, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Timings {
+    // Estimated on a separate probe connection, if one was asked for and
+    // could be made
+    pub connection: Option<Connection>,
+    pub first_byte: Duration,
+    pub download: Duration,
+}
+
+#[derive(Clone, Copy, Serialize, Deserialize)]
+pub struct Connection {
+    pub dns: Duration,
+    pub connect: Duration,
+    pub tls: Duration,
+}
+
+impl Timings {
+    pub fn total(&self) -> Duration {
+        self.first_byte + self.download
+    }
+
+    // Each phase's name, start and length. Setting up the connection happens
+    // while waiting for the response, so those phases overlap the wait, and
+    // their names are marked with `~` as they're estimates.
+    pub fn phases(&self) -> Vec<(&'static str, Duration, Duration)> {
+        let mut phases = vec![];
+        if let Some(connection) = self.connection {
+            let start = Duration::ZERO;
+            phases.push(("~DNS", start, connection.dns));
+            let start = start + connection.dns;
+            phases.push(("~Connect", start, connection.connect));
+            phases.push(("~TLS", start + connection.connect, connection.tls));
+        }
+        phases.push(("Waiting", Duration::ZERO, self.first_byte));
+        phases.push(("Download", self.first_byte, self.download));
+        phases
+    }
+}
+
+// DNS, TCP connect and the TLS handshake are measured on a probe connection
+// to the URL's host. It's only an estimate of what the request went through,
+// and it's skipped when the request goes through a proxy, since it doesn't
+// connect to the host directly then.
+pub fn probe(url: &Url) -> Result<Connection, Box<dyn Error>> {
+    let host = url.host_str().ok_or("the URL has no host")?;
+    let port = url.port_or_known_default().ok_or("the URL has no port")?;
+    let tls = url.scheme() == "https";
+    if proxied(url.scheme(), host) {
+        return Err("the request goes through a proxy".into());
+    }
+
+    let start = Instant::now();
+    let addr = (host, port)
+        .to_socket_addrs()?
+        .next()
+        .ok_or_else(|| format!("could not resolve {}", host))?;
+    let dns = start.elapsed();
+
+    let start = Instant::now();
+    let stream = TcpStream::connect_timeout(&addr, CONNECT_TIMEOUT)?;
+    let connect = start.elapsed();
+
+    let start = Instant::now();
+    if tls {
+        native_tls::TlsConnector::new()?.connect(host, stream)?;
+    }
+    let tls = start.elapsed();
+
+    Ok(Connection { dns, connect, tls })
+}
+
+// Whether the proxy environment variables send requests for this scheme and
+// host through a proxy, the way reqwest reads them
+fn proxied(scheme: &str, host: &str) -> bool {
+    let proxies: &[&str] = match scheme {
+        "https" => &["HTTPS_PROXY", "https_proxy", "ALL_PROXY", "all_proxy"],
+        _ => &["HTTP_PROXY", "http_proxy", "ALL_PROXY", "all_proxy"],
+    };
+    let variable = |name: &str| env::var(name).ok().filter(|value| !value.is_empty());
+    proxies.iter().any(|name| variable(name).is_some())
+        && !variable("NO_PROXY")
+            .or_else(|| variable("no_proxy"))
+            .is_some_and(|no_proxy| bypasses(&no_proxy, host))
+}
+
+// `NO_PROXY` lists hosts, and domains whose subdomains are included too,
+// separated by commas. `*` matches every host.
+fn bypasses(no_proxy: &str, host: &str) -> bool {
+    let host = host.trim_start_matches('[').trim_end_matches(']');
+    no_proxy.split(',').map(str::trim).any(|entry| {
+        let domain = entry.trim_start_matches('.');
+        entry == "*"
+            || (!domain.is_empty()
+                && (host.eq_ignore_ascii_case(domain)
+                    || host
+                        .to_ascii_lowercase()
+                        .ends_with(&format!(".{}", domain.to_ascii_lowercase()))))
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn reads_no_proxy_lists() {
+        let no_proxy = "localhost, .internal,example.com,::1";
+        assert!(bypasses(no_proxy, "localhost"));
+        assert!(bypasses(no_proxy, "api.internal"));
+        assert!(bypasses(no_proxy, "internal"));
+        assert!(bypasses(no_proxy, "EXAMPLE.com"));
+        assert!(bypasses(no_proxy, "www.example.com"));
+        assert!(bypasses(no_proxy, "[::1]"));
+        assert!(!bypasses(no_proxy, "notexample.com"));
+        assert!(!bypasses(no_proxy, "example.org"));
+        assert!(!bypasses("", "localhost"));
+        assert!(bypasses("*", "example.org"));
+    }
+}
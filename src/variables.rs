@@ -0,0 +1,126 @@
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::error::Error;
+use std::fs;
+use std::path::Path;
+
+pub type Variables = BTreeMap<String, String>;
+
+#[derive(Serialize, Deserialize, Default, Clone)]
+pub struct Environment {
+    pub name: String,
+    #[serde(default)]
+    pub variables: Variables,
+}
+
+impl Environment {
+    pub fn load(path: &Path) -> Result<Environment, Box<dyn Error>> {
+        let text = fs::read_to_string(path)
+            .map_err(|e| format!("could not read {}: {}", path.display(), e))?;
+        Ok(serde_json::from_str(&text)
+            .map_err(|e| format!("invalid environment {}: {}", path.display(), e))?)
+    }
+
+    // Environments are looked up by name in `environments/<name>.json`, but
+    // a path to any environment file works too
+    pub fn find(name: &str) -> Result<Environment, Box<dyn Error>> {
+        let path = Path::new(name);
+        if path.is_file() {
+            return Environment::load(path);
+        }
+        Environment::load(&Path::new("environments").join(format!("{}.json", name)))
+    }
+}
+
+// Names of the variables used in the text
+pub fn references(text: &str) -> Vec<String> {
+    let mut names = vec![];
+    let mut rest = text;
+    while let Some(start) = rest.find("{{") {
+        match rest[start..].find("}}") {
+            Some(end) => {
+                names.push(rest[start + 2..start + end].trim().to_string());
+                rest = &rest[start + end + 2..];
+            }
+            None => break,
+        }
+    }
+    names
+}
+
+// Replaces every `{{name}}` in the text with the variable's value. Like in
+// Postman, placeholders without a value and an unclosed `{{` are left as they
+// are, so text such as a Mustache template can still be sent.
+pub fn substitute(text: &str, variables: &Variables) -> String {
+    let mut result = String::with_capacity(text.len());
+    let mut rest = text;
+
+    while let Some(start) = rest.find("{{") {
+        let end = match rest[start..].find("}}") {
+            Some(end) => start + end,
+            None => break,
+        };
+        result.push_str(&rest[..start]);
+        match variables.get(rest[start + 2..end].trim()) {
+            Some(value) => result.push_str(value),
+            None => result.push_str(&rest[start..end + 2]),
+        }
+        rest = &rest[end + 2..];
+    }
+    result.push_str(rest);
+
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn variables() -> Variables {
+        [("host", "localhost:8080"), ("id", "42")]
+            .iter()
+            .map(|(name, value)| (name.to_string(), value.to_string()))
+            .collect()
+    }
+
+    #[test]
+    fn finds_references() {
+        assert_eq!(
+            references("http://{{host}}/users/{{ id }}?q={{unclosed"),
+            ["host", "id"]
+        );
+        assert!(references("no variables {here}").is_empty());
+    }
+
+    #[test]
+    fn substitutes_variables() {
+        assert_eq!(
+            substitute("http://{{host}}/users/{{ id }}", &variables()),
+            "http://localhost:8080/users/42"
+        );
+        assert_eq!(substitute("plain {text}", &variables()), "plain {text}");
+    }
+
+    #[test]
+    fn substitution_is_not_recursive() {
+        let mut variables = variables();
+        variables.insert("template".to_string(), "{{id}}".to_string());
+        assert_eq!(substitute("{{template}}", &variables), "{{id}}");
+    }
+
+    #[test]
+    fn leaves_undefined_and_unclosed_placeholders() {
+        assert_eq!(
+            substitute("{{token}} for {{ id }}", &variables()),
+            "{{token}} for 42"
+        );
+        assert_eq!(
+            substitute("{{host}}/{{id", &variables()),
+            "localhost:8080/{{id"
+        );
+        assert_eq!(
+            substitute("Hello {{#user}}{{name}}{{/user}}", &variables()),
+            "Hello {{#user}}{{name}}{{/user}}"
+        );
+    }
+}
@@ -0,0 +1,396 @@
+use crate::collection::{Collection, CollectionItem};
+use crate::runner::{self, RequestResult, Undefined};
+use crate::variables::Variables;
+use petgraph::{
+    algo,
+    graph::{DiGraph, NodeIndex},
+    visit::EdgeRef,
+    Direction,
+};
+use serde::{Deserialize, Serialize};
+use std::panic::{self, AssertUnwindSafe};
+use std::sync::mpsc;
+use std::thread;
+use std::time::Instant;
+
+#[derive(Serialize, Deserialize, Clone)]
+pub struct Workflow {
+    pub name: String,
+    pub steps: Vec<WorkflowStep>,
+}
+
+// A step runs the collection request at `request` (e.g. `auth/login`). It
+// waits for the steps listed in `after`, and for any step that extracts a
+// variable its request uses.
+#[derive(Serialize, Deserialize, Clone)]
+pub struct WorkflowStep {
+    pub id: String,
+    pub request: String,
+    #[serde(default)]
+    pub after: Vec<String>,
+}
+
+pub struct StepNode {
+    pub id: String,
+    pub item: CollectionItem,
+}
+
+pub enum Dependency {
+    Data(Vec<String>),
+    Order,
+}
+
+pub struct WorkflowGraph {
+    pub name: String,
+    pub graph: DiGraph<StepNode, Dependency>,
+}
+
+#[derive(Clone, Copy, PartialEq)]
+pub enum StepStatus {
+    Pending,
+    Running,
+    Passed,
+    Failed,
+    Skipped,
+}
+
+pub struct WorkflowRun {
+    pub results: Vec<RequestResult>,
+    pub environment: Variables,
+    pub runtime: Variables,
+    pub duration_ms: u128,
+}
+
+impl WorkflowGraph {
+    pub fn build(collection: &Collection, name: &str) -> Result<WorkflowGraph, String> {
+        let workflow = collection
+            .workflows
+            .iter()
+            .find(|workflow| workflow.name == name)
+            .ok_or_else(|| format!("no workflow named {} in {}", name, collection.name))?;
+        let items = collection.items(None)?;
+
+        let mut graph = DiGraph::new();
+        for (i, step) in workflow.steps.iter().enumerate() {
+            if workflow.steps[..i].iter().any(|other| other.id == step.id) {
+                return Err(format!("step {} is defined more than once", step.id));
+            }
+            let item = items
+                .iter()
+                .find(|item| item.path == step.request)
+                .ok_or_else(|| format!("step {}: no request at {}", step.id, step.request))?;
+            graph.add_node(StepNode {
+                id: step.id.clone(),
+                item: CollectionItem {
+                    path: step.id.clone(),
+                    request: item.request.clone(),
+                    variables: item.variables.clone(),
+                },
+            });
+        }
+
+        let find = |id: &str| {
+            graph
+                .node_indices()
+                .find(|node| graph[*node].id == id)
+                .ok_or_else(|| format!("unknown step {}", id))
+        };
+        let mut edges = vec![];
+        for (step, node) in workflow.steps.iter().zip(graph.node_indices()) {
+            for before in &step.after {
+                edges.push((find(before)?, node, Dependency::Order));
+            }
+
+            let used = graph[node].item.request.references();
+            for other in graph.node_indices().filter(|other| *other != node) {
+                let shared: Vec<String> = graph[other]
+                    .item
+                    .request
+                    .extract
+                    .iter()
+                    .map(|extraction| extraction.variable.clone())
+                    .filter(|variable| used.contains(variable))
+                    .collect();
+                if !shared.is_empty() {
+                    edges.push((other, node, Dependency::Data(shared)));
+                }
+            }
+        }
+        for (from, to, dependency) in edges {
+            graph.add_edge(from, to, dependency);
+        }
+
+        if let Err(cycle) = algo::toposort(&graph, None) {
+            return Err(format!(
+                "workflow {} has a cycle through step {}",
+                name,
+                graph[cycle.node_id()].id
+            ));
+        }
+
+        Ok(WorkflowGraph {
+            name: name.to_string(),
+            graph,
+        })
+    }
+
+    // Groups steps by the length of the longest chain of dependencies
+    // leading to them, so every step comes after the ones it needs
+    pub fn layers(&self) -> Vec<Vec<NodeIndex>> {
+        let mut depth = vec![0; self.graph.node_count()];
+        for node in algo::toposort(&self.graph, None).unwrap_or_default() {
+            for next in self.graph.neighbors_directed(node, Direction::Outgoing) {
+                depth[next.index()] = depth[next.index()].max(depth[node.index()] + 1);
+            }
+        }
+
+        let mut layers = vec![vec![]; depth.iter().max().map_or(0, |max| max + 1)];
+        for node in self.graph.node_indices() {
+            layers[depth[node.index()]].push(node);
+        }
+        layers
+    }
+
+    pub fn dependencies(&self, node: NodeIndex) -> impl Iterator<Item = (NodeIndex, &Dependency)> {
+        self.graph
+            .edges_directed(node, Direction::Incoming)
+            .map(|edge| (edge.source(), edge.weight()))
+    }
+}
+
+// Runs every step once the steps it depends on have passed, with independent
+// steps running in parallel. After a failure no new steps are started, and
+// anything that hasn't run is skipped.
+pub fn execute(
+    workflow: &WorkflowGraph,
+    environment: Variables,
+    runtime: Variables,
+    undefined: Undefined,
+    mut on_update: impl FnMut(NodeIndex, StepStatus, Option<&RequestResult>),
+) -> WorkflowRun {
+    let start = Instant::now();
+    let graph = &workflow.graph;
+    let mut statuses = vec![StepStatus::Pending; graph.node_count()];
+    let mut results = vec![];
+    let mut environment = environment;
+    let mut runtime = runtime;
+    let mut running = 0;
+    let mut failed = false;
+    let (tx, rx) = mpsc::channel();
+
+    loop {
+        if !failed {
+            for node in graph.node_indices() {
+                let ready = statuses[node.index()] == StepStatus::Pending
+                    && graph
+                        .neighbors_directed(node, Direction::Incoming)
+                        .all(|before| statuses[before.index()] == StepStatus::Passed);
+                if !ready {
+                    continue;
+                }
+
+                statuses[node.index()] = StepStatus::Running;
+                on_update(node, StepStatus::Running, None);
+                running += 1;
+
+                let item = graph[node].item.clone();
+                let mut step_environment = environment.clone();
+                let mut step_runtime = runtime.clone();
+                let tx = tx.clone();
+                thread::spawn(move || {
+                    // A step that panics still has to report back, or the
+                    // workflow would wait for it forever
+                    let result = panic::catch_unwind(AssertUnwindSafe(|| {
+                        runner::run_item(&item, &mut step_environment, &mut step_runtime, undefined)
+                    }))
+                    .unwrap_or_else(|panic| {
+                        let message = panic
+                            .downcast_ref::<&str>()
+                            .map(|message| message.to_string())
+                            .or_else(|| panic.downcast_ref::<String>().cloned())
+                            .unwrap_or_default();
+                        runner::failed(&item, format!("the step panicked: {}", message))
+                    });
+                    let extracted: Vec<_> = item
+                        .request
+                        .extract
+                        .iter()
+                        .map(|extraction| &extraction.variable)
+                        .collect();
+                    let changed = |variables: Variables| -> Variables {
+                        variables
+                            .into_iter()
+                            .filter(|(name, _)| extracted.contains(&name))
+                            .collect()
+                    };
+                    let _ = tx.send((
+                        node,
+                        result,
+                        changed(step_environment),
+                        changed(step_runtime),
+                    ));
+                });
+            }
+        }
+
+        if running == 0 {
+            break;
+        }
+
+        // `tx` is still held here, so this can't fail
+        let (node, result, new_environment, new_runtime) = rx.recv().unwrap();
+        running -= 1;
+        environment.extend(new_environment);
+        runtime.extend(new_runtime);
+
+        let status = if result.passed() {
+            StepStatus::Passed
+        } else {
+            failed = true;
+            StepStatus::Failed
+        };
+        statuses[node.index()] = status;
+        on_update(node, status, Some(&result));
+        results.push(result);
+    }
+
+    for node in graph.node_indices() {
+        if statuses[node.index()] == StepStatus::Pending {
+            statuses[node.index()] = StepStatus::Skipped;
+            let result = runner::skipped(&graph[node].item);
+            on_update(node, StepStatus::Skipped, Some(&result));
+            results.push(result);
+        }
+    }
+
+    WorkflowRun {
+        results,
+        environment,
+        runtime,
+        duration_ms: start.elapsed().as_millis(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn collection(workflow: &str) -> Collection {
+        let mut collection: Collection = serde_json::from_str(
+            r#"{
+                "name": "API",
+                "requests": [
+                    {
+                        "name": "login",
+                        "method": "POST",
+                        "url": "http://a.test/login",
+                        "extract": ["token = json $.token"]
+                    },
+                    {"name": "health", "url": "http://a.test/health"}
+                ],
+                "folders": [{
+                    "name": "users",
+                    "requests": [
+                        {
+                            "name": "list",
+                            "url": "http://a.test/users",
+                            "headers": [["Authorization", "Bearer {{token}}"]]
+                        },
+                        {"name": "get", "url": "http://a.test/users/1"}
+                    ]
+                }]
+            }"#,
+        )
+        .unwrap();
+        collection.workflows = vec![serde_json::from_str(workflow).unwrap()];
+        collection
+    }
+
+    fn build(steps: &str) -> Result<WorkflowGraph, String> {
+        let workflow = format!(r#"{{"name": "flow", "steps": {}}}"#, steps);
+        WorkflowGraph::build(&collection(&workflow), "flow")
+    }
+
+    fn layer_ids(graph: &WorkflowGraph) -> Vec<Vec<&str>> {
+        graph
+            .layers()
+            .iter()
+            .map(|layer| {
+                layer
+                    .iter()
+                    .map(|node| graph.graph[*node].id.as_str())
+                    .collect()
+            })
+            .collect()
+    }
+
+    #[test]
+    fn orders_steps_by_their_dependencies() {
+        let graph = build(
+            r#"[
+                {"id": "users", "request": "users/list"},
+                {"id": "user", "request": "users/get", "after": ["users", "health"]},
+                {"id": "login", "request": "login"},
+                {"id": "health", "request": "health"}
+            ]"#,
+        )
+        .unwrap();
+        assert_eq!(
+            layer_ids(&graph),
+            [vec!["login", "health"], vec!["users"], vec!["user"]]
+        );
+    }
+
+    #[test]
+    fn links_steps_through_the_variables_they_share() {
+        let graph = build(
+            r#"[
+                {"id": "login", "request": "login"},
+                {"id": "users", "request": "users/list"}
+            ]"#,
+        )
+        .unwrap();
+        let users = graph.graph.node_indices().nth(1).unwrap();
+        let dependencies: Vec<_> = graph.dependencies(users).collect();
+        assert_eq!(dependencies.len(), 1);
+        match dependencies[0] {
+            (login, Dependency::Data(variables)) => {
+                assert_eq!(graph.graph[login].id, "login");
+                assert_eq!(variables, &["token"]);
+            }
+            _ => panic!("expected a data dependency"),
+        }
+    }
+
+    #[test]
+    fn rejects_invalid_workflows() {
+        let error = |steps: &str| build(steps).err().unwrap();
+        assert_eq!(
+            error(r#"[{"id": "a", "request": "missing"}]"#),
+            "step a: no request at missing"
+        );
+        assert_eq!(
+            error(r#"[{"id": "a", "request": "health", "after": ["b"]}]"#),
+            "unknown step b"
+        );
+        assert!(error(
+            r#"[
+                {"id": "a", "request": "health", "after": ["b"]},
+                {"id": "b", "request": "users/get", "after": ["a"]}
+            ]"#
+        )
+        .starts_with("workflow flow has a cycle through step "));
+        assert_eq!(
+            error(
+                r#"[
+                    {"id": "a", "request": "health"},
+                    {"id": "a", "request": "users/get"}
+                ]"#
+            ),
+            "step a is defined more than once"
+        );
+        assert!(
+            WorkflowGraph::build(&collection(r#"{"name": "x", "steps": []}"#), "flow").is_err()
+        );
+    }
+}
@@ -0,0 +1,192 @@
+// Runs collections against a small HTTP server on a local port, the way
+// `http_request_tool run` does
+
+mod common;
+
+use common::serve;
+use http_request_tool::collection::Collection;
+use http_request_tool::executor::Executor;
+use http_request_tool::runner::{self, Undefined};
+use http_request_tool::variables::Environment;
+
+fn collection(json: &str) -> Collection {
+    serde_json::from_str(json).unwrap()
+}
+
+#[test]
+fn runs_a_collection_in_order() {
+    let base = serve();
+    let collection = collection(
+        r#"{
+            "name": "Users",
+            "variables": {"id": "7"},
+            "requests": [{
+                "name": "login",
+                "method": "POST",
+                "url": "{{base}}/login",
+                "headers": [["Content-Type", "application/json"]],
+                "body": "{\"user\":\"{{user}}\"}",
+                "assertions": ["status == 200", "header Set-Cookie exists"],
+                "extract": ["token = json $.token", "sid = cookie sid"]
+            }],
+            "folders": [{
+                "name": "users",
+                "variables": {"id": "42"},
+                "requests": [{
+                    "name": "get",
+                    "url": "{{base}}/users/{{id}}",
+                    "headers": [["Authorization", "Bearer {{token}}"], ["Cookie", "sid={{sid}}"]],
+                    "assertions": [
+                        "status == 200",
+                        "json $.id == 42",
+                        "json $.name matches ^A",
+                        "body contains \"Ann\"",
+                        "time < 5000ms"
+                    ]
+                }]
+            }]
+        }"#,
+    );
+    let environment = Environment {
+        name: "local".to_string(),
+        variables: [("base", base.as_str()), ("user", "ann")]
+            .iter()
+            .map(|(name, value)| (name.to_string(), value.to_string()))
+            .collect(),
+    };
+
+    let mut finished = vec![];
+    let report = runner::run(
+        &collection,
+        None,
+        Some(&environment),
+        Undefined::Warn,
+        |result| finished.push(result.path.clone()),
+    )
+    .unwrap();
+
+    assert_eq!(finished, ["login", "users/get"]);
+    assert_eq!(report.collection, "Users");
+    assert_eq!(report.environment.as_deref(), Some("local"));
+    assert_eq!(report.failures(), 0);
+    let get = &report.results[1];
+    assert_eq!(get.status, Some(200));
+    assert_eq!(get.url, format!("{}/users/42", base));
+    assert_eq!(get.assertions.len(), 5);
+}
+
+#[test]
+fn reports_failures_and_errors() {
+    let base = serve();
+    let collection = collection(&format!(
+        r#"{{
+            "name": "Failures",
+            "requests": [
+                {{
+                    "name": "login",
+                    "method": "POST",
+                    "url": "{base}/login",
+                    "body": "{{\"user\":\"bob\"}}",
+                    "assertions": ["status == 200"],
+                    "extract": ["token = json $.token"]
+                }},
+                {{"name": "user", "url": "{base}/users/{{{{token}}}}"}},
+                {{"name": "closed", "url": "http://127.0.0.1:9/"}}
+            ]
+        }}"#,
+        base = base
+    ));
+
+    let report = runner::run(&collection, None, None, Undefined::Warn, |_| {}).unwrap();
+
+    assert_eq!(report.failures(), 2);
+    let login = &report.results[0];
+    assert_eq!(login.status, Some(401));
+    assert_eq!(login.assertions[0].message, "status was 401");
+    assert_eq!(login.assertions[1].message, "$.token not found");
+    // Nothing was captured, so the placeholder is sent as written
+    let user = &report.results[1];
+    assert_eq!(user.status, Some(401));
+    assert_eq!(user.url, format!("{}/users/{{{{token}}}}", base));
+    assert_eq!(
+        user.warnings,
+        ["undefined variables sent as written: token"]
+    );
+    let closed = &report.results[2];
+    assert_eq!(closed.status, None);
+    assert!(closed.error.is_some());
+
+    // Unless undefined variables are errors
+    let report = runner::run(&collection, None, None, Undefined::Fail, |_| {}).unwrap();
+    let user = &report.results[1];
+    assert_eq!(user.status, None);
+    assert_eq!(user.error.as_deref(), Some("undefined variables: token"));
+}
+
+#[test]
+fn runs_one_folder() {
+    let base = serve();
+    let collection = collection(&format!(
+        r#"{{
+            "name": "Folders",
+            "requests": [{{"name": "root", "url": "{base}/echo/root"}}],
+            "folders": [{{
+                "name": "echo",
+                "requests": [{{
+                    "name": "patch",
+                    "method": "PATCH",
+                    "url": "{base}/echo",
+                    "query": [["q", "1"]],
+                    "headers": [["X-Test", "yes"]],
+                    "assertions": ["body contains \"PATCH /echo?q=1 yes\""]
+                }}]
+            }}]
+        }}"#,
+        base = base
+    ));
+
+    let report = runner::run(&collection, Some("echo"), None, Undefined::Warn, |_| {}).unwrap();
+
+    assert_eq!(report.results.len(), 1);
+    assert!(report.results[0].passed());
+    assert!(runner::run(&collection, Some("missing"), None, Undefined::Warn, |_| {}).is_err());
+}
+
+#[test]
+fn executor_keeps_captured_values() {
+    let base = serve();
+    let collection = collection(&format!(
+        r#"{{
+            "name": "Executor",
+            "requests": [
+                {{
+                    "name": "login",
+                    "method": "POST",
+                    "url": "{base}/login",
+                    "body": "{{\"user\":\"ann\"}}",
+                    "extract": ["env token = json $.token", "sid = cookie sid"]
+                }},
+                {{
+                    "name": "get",
+                    "url": "{base}/users/1",
+                    "headers": [["Authorization", "Bearer {{{{token}}}}"], ["Cookie", "sid={{{{sid}}}}"]],
+                    "assertions": ["status == 200"]
+                }}
+            ]
+        }}"#,
+        base = base
+    ));
+
+    let mut executor = Executor::new(None);
+    let login = executor
+        .execute(&collection.item("login").unwrap())
+        .unwrap();
+    assert!(login.passed());
+    assert_eq!(executor.environment.get("token").unwrap(), "t0k");
+    assert_eq!(executor.runtime.get("sid").unwrap(), "abc");
+
+    let get = executor.execute(&collection.item("get").unwrap()).unwrap();
+    assert!(get.passed());
+    assert_eq!(get.request.headers[0].1, "Bearer t0k");
+    assert_eq!(get.response.body, r#"{"id":1,"name":"Ann"}"#);
+}
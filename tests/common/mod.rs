@@ -0,0 +1,95 @@
+// A small HTTP server on a local port for the tests to send requests to
+
+use std::io::{BufRead, BufReader, Read, Write};
+use std::net::{TcpListener, TcpStream};
+use std::thread;
+
+// Answers on a free port until the test ends, and returns the base URL.
+//
+//   POST /login        {"token": "t0k"} and a session cookie
+//   GET  /users/<id>   the user, if the request has the token
+//   /echo              the request's method, path and `X-Test` header
+pub fn serve() -> String {
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let address = listener.local_addr().unwrap();
+    thread::spawn(move || {
+        for stream in listener.incoming() {
+            handle(stream.unwrap());
+        }
+    });
+    format!("http://{}", address)
+}
+
+fn handle(stream: TcpStream) {
+    let mut reader = BufReader::new(stream.try_clone().unwrap());
+    let mut request_line = String::new();
+    if reader.read_line(&mut request_line).unwrap_or(0) == 0 {
+        return;
+    }
+    let mut headers = vec![];
+    loop {
+        let mut line = String::new();
+        reader.read_line(&mut line).unwrap();
+        match line.trim_end().split_once(':') {
+            Some((name, value)) => {
+                headers.push((name.to_ascii_lowercase(), value.trim().to_string()))
+            }
+            None => break,
+        }
+    }
+    let header = |name: &str| {
+        headers
+            .iter()
+            .find(|(header, _)| header == name)
+            .map_or("", |(_, value)| value.as_str())
+    };
+    let length = header("content-length").parse().unwrap_or(0);
+    let mut body = vec![0; length];
+    reader.read_exact(&mut body).unwrap();
+    let body = String::from_utf8(body).unwrap();
+
+    let mut parts = request_line.split_whitespace();
+    let method = parts.next().unwrap_or("");
+    let path = parts.next().unwrap_or("");
+    let (status, extra, response) = match (method, path) {
+        ("POST", "/login") if body.contains("\"user\":\"ann\"") => (
+            "200 OK",
+            "Set-Cookie: sid=abc; HttpOnly\r\n",
+            r#"{"token":"t0k"}"#.to_string(),
+        ),
+        ("POST", "/login") => (
+            "401 Unauthorized",
+            "",
+            r#"{"error":"bad user"}"#.to_string(),
+        ),
+        ("GET", path) if path.starts_with("/users/") => {
+            if header("authorization") == "Bearer t0k" && header("cookie") == "sid=abc" {
+                let id = &path["/users/".len()..];
+                ("200 OK", "", format!(r#"{{"id":{},"name":"Ann"}}"#, id))
+            } else {
+                (
+                    "401 Unauthorized",
+                    "",
+                    r#"{"error":"no token"}"#.to_string(),
+                )
+            }
+        }
+        (method, path) if path.starts_with("/echo") => (
+            "200 OK",
+            "",
+            format!("{} {} {}", method, path, header("x-test")),
+        ),
+        _ => ("404 Not Found", "", String::new()),
+    };
+
+    let mut stream = reader.into_inner();
+    write!(
+        stream,
+        "HTTP/1.1 {}\r\nContent-Type: application/json\r\n{}Content-Length: {}\r\nConnection: close\r\n\r\n{}",
+        status,
+        extra,
+        response.len(),
+        response
+    )
+    .unwrap();
+}
@@ -0,0 +1,131 @@
+// Runs workflows against a small HTTP server on a local port
+
+mod common;
+
+use common::serve;
+use http_request_tool::collection::Collection;
+use http_request_tool::runner::Undefined;
+use http_request_tool::variables::Variables;
+use http_request_tool::workflow::{self, StepStatus, WorkflowGraph, WorkflowRun};
+
+fn collection(base: &str) -> Collection {
+    let json = r#"{
+        "name": "API",
+        "requests": [
+            {
+                "name": "login",
+                "method": "POST",
+                "url": "{{base}}/login",
+                "body": "{\"user\":\"ann\"}",
+                "extract": ["env token = json $.token", "sid = cookie sid"]
+            },
+            {
+                "name": "user",
+                "url": "{{base}}/users/1",
+                "headers": [["Authorization", "Bearer {{token}}"], ["Cookie", "sid={{sid}}"]],
+                "assertions": ["status == 200"]
+            },
+            {"name": "echo", "url": "{{base}}/echo", "assertions": ["status == 200"]},
+            {"name": "broken", "url": "{{base}}/echo", "assertions": ["status == 500"]}
+        ],
+        "workflows": [
+            {
+                "name": "chain",
+                "steps": [
+                    {"id": "user", "request": "user"},
+                    {"id": "login", "request": "login"}
+                ]
+            },
+            {
+                "name": "diamond",
+                "steps": [
+                    {"id": "d", "request": "echo", "after": ["b", "c"]},
+                    {"id": "b", "request": "echo", "after": ["a"]},
+                    {"id": "c", "request": "echo", "after": ["a"]},
+                    {"id": "a", "request": "echo"}
+                ]
+            },
+            {
+                "name": "failing",
+                "steps": [
+                    {"id": "broken", "request": "broken"},
+                    {"id": "after", "request": "echo", "after": ["broken"]},
+                    {"id": "independent", "request": "echo"}
+                ]
+            }
+        ]
+    }"#;
+    let mut collection: Collection = serde_json::from_str(json).unwrap();
+    collection
+        .variables
+        .insert("base".to_string(), base.to_string());
+    collection
+}
+
+// Runs a workflow, returning the run and every status change in order
+fn execute(name: &str) -> (WorkflowRun, Vec<(String, StepStatus)>) {
+    let collection = collection(&serve());
+    let graph = WorkflowGraph::build(&collection, name).unwrap();
+    let mut updates = vec![];
+    let run = workflow::execute(
+        &graph,
+        Variables::new(),
+        Variables::new(),
+        Undefined::Warn,
+        |node, status, _| updates.push((graph.graph[node].id.clone(), status)),
+    );
+    (run, updates)
+}
+
+fn position(updates: &[(String, StepStatus)], id: &str, status: StepStatus) -> usize {
+    updates
+        .iter()
+        .position(|update| update.0 == id && update.1 == status)
+        .unwrap()
+}
+
+#[test]
+fn passes_captured_values_to_later_steps() {
+    let (run, updates) = execute("chain");
+
+    assert!(
+        position(&updates, "login", StepStatus::Passed)
+            < position(&updates, "user", StepStatus::Running)
+    );
+    assert!(run.results.iter().all(|result| result.passed()));
+    assert_eq!(run.environment.get("token").unwrap(), "t0k");
+    assert_eq!(run.runtime.get("sid").unwrap(), "abc");
+}
+
+#[test]
+fn fans_out_and_back_in() {
+    let (run, updates) = execute("diamond");
+
+    let a = position(&updates, "a", StepStatus::Passed);
+    let b = position(&updates, "b", StepStatus::Running);
+    let c = position(&updates, "c", StepStatus::Running);
+    let d = position(&updates, "d", StepStatus::Running);
+    assert!(a < b && a < c);
+    // `b` and `c` are independent, so both start before either finishes
+    assert!(b < position(&updates, "c", StepStatus::Passed));
+    assert!(c < position(&updates, "b", StepStatus::Passed));
+    assert!(position(&updates, "b", StepStatus::Passed) < d);
+    assert!(position(&updates, "c", StepStatus::Passed) < d);
+    assert_eq!(run.results.len(), 4);
+    assert_eq!(run.results.last().unwrap().path, "d");
+}
+
+#[test]
+fn stops_after_a_failure() {
+    let (run, updates) = execute("failing");
+
+    assert!(updates.contains(&("broken".to_string(), StepStatus::Failed)));
+    assert!(updates.contains(&("independent".to_string(), StepStatus::Passed)));
+    assert!(!updates.contains(&("after".to_string(), StepStatus::Running)));
+    let after = run
+        .results
+        .iter()
+        .find(|result| result.path == "after")
+        .unwrap();
+    assert!(after.skipped);
+}